@@ -1,13 +1,20 @@
-use crate::config::Config;
+use crate::config::{Config, Service};
+use crate::metrics;
 use anyhow::{anyhow, Context, Result};
+use async_trait::async_trait;
 use base64::{engine::general_purpose::STANDARD as BASE64_STANDARD, Engine as _};
 use log::{debug, info, warn};
-use reqwest::{multipart, Body, Client, StatusCode};
+use reqwest::{multipart, Body, Client, Response, StatusCode};
 use serde::{Deserialize, Serialize};
 use serde_json::Value; // For handling flexible JSON structures
-use std::path::Path;
+use once_cell::sync::Lazy;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use std::time::Duration;
+use thiserror::Error;
 use tokio::fs::File;
+use tokio::sync::Mutex as AsyncMutex;
 use tokio::time::sleep;
 use tokio_util::codec::{BytesCodec, FramedRead};
 
@@ -24,7 +31,222 @@ const ELEVENLABS_MODEL: &str = "scribe_v1"; // Or allow configuration
 
 // OpenAI constants
 const OPENAI_API_URL: &str = "https://api.openai.com/v1/audio/transcriptions";
-const OPENAI_MODEL: &str = "gpt-4o-transcribe"; 
+const OPENAI_MODEL: &str = "gpt-4o-transcribe";
+
+/// One segment of a `Transcription`, with the start/end offsets (seconds,
+/// relative to the start of the audio) the backend reported for it. Backends
+/// that only return plain text leave `segments` empty on the `Transcription`
+/// that wraps them instead of fabricating timing.
+#[derive(Debug, Clone)]
+pub struct Segment {
+    pub start: f64,
+    pub end: f64,
+    pub text: String,
+}
+
+/// Result of a transcription: the full text, plus per-segment timing when
+/// the backend reported any (Replicate's whisper `segments`, OpenAI's
+/// `verbose_json` segments, ElevenLabs' word timestamps). Callers that only
+/// want the plain string — most of them — can use `text()` instead of
+/// reaching into the struct; callers building subtitles or a click-to-seek
+/// UI can walk `segments`.
+#[derive(Debug, Clone, Default)]
+pub struct Transcription {
+    pub text: String,
+    pub segments: Vec<Segment>,
+}
+
+impl Transcription {
+    /// Wraps a plain string with no segment timing, for backends (Deepgram,
+    /// the local whisper.cpp path) that don't report any.
+    pub fn from_text(text: String) -> Self {
+        Self { text, segments: Vec::new() }
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+}
+
+/// Why a transcription attempt failed, so callers can tell "invalid API
+/// key" (never retry, surface to the user) apart from "service
+/// unavailable" (retryable) apart from "bad audio file" (a fatal local
+/// error) instead of pattern-matching error strings. Fatal variants are
+/// returned as the root cause of the `anyhow::Error` from
+/// `TranscriptionProvider::transcribe`; recover one with
+/// `err.downcast_ref::<TranscribeError>()`.
+#[derive(Debug, Error)]
+pub enum TranscribeError {
+    #[error("authentication failed: {0}")]
+    Auth(String),
+    #[error("rate limited: {0}")]
+    RateLimited(String),
+    #[error("server error: {0}")]
+    ServerError(String),
+    #[error("request timed out: {0}")]
+    Timeout(String),
+    #[error("bad input: {0}")]
+    BadInput(String),
+    #[error("failed to parse response: {0}")]
+    Parse(String),
+    /// Catch-all for a `reqwest::Error` that isn't a timeout/connect or
+    /// decode failure — a bad request builder, a redirect error, the
+    /// connection dropping mid-body, etc. These aren't known to be
+    /// transient, so treat them as fatal rather than retrying.
+    #[error("request failed: {0}")]
+    Request(String),
+}
+
+impl TranscribeError {
+    /// Whether `with_retries` should retry this failure instead of
+    /// surfacing it to the caller immediately.
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            TranscribeError::RateLimited(_) | TranscribeError::ServerError(_) | TranscribeError::Timeout(_)
+        )
+    }
+
+    /// Maps an HTTP response status into a category, per the conventions
+    /// these providers' APIs share.
+    fn from_status(status: StatusCode, body: String) -> Self {
+        match status.as_u16() {
+            401 | 403 => TranscribeError::Auth(body),
+            400 => TranscribeError::BadInput(body),
+            429 => TranscribeError::RateLimited(body),
+            s if (500..600).contains(&s) => TranscribeError::ServerError(body),
+            _ => TranscribeError::ServerError(format!("{} {}", status, body)),
+        }
+    }
+}
+
+/// One remote speech-to-text backend. `Local` isn't a `TranscriptionProvider`
+/// since it transcribes `&[f32]` samples directly rather than uploading a
+/// file (see `transcribe_local`); everything that sends `audio_path`
+/// somewhere over HTTP implements this instead.
+#[async_trait]
+pub trait TranscriptionProvider: Send + Sync {
+    async fn transcribe(&self, config: &Config, audio_path: &Path) -> Result<Transcription>;
+}
+
+/// Picks the `TranscriptionProvider` for a remote `Service`. Panics on
+/// `Service::Local`, which callers are expected to special-case before
+/// reaching here (see `whisper_dictation::transcribe`).
+pub fn provider_for(service: Service) -> Box<dyn TranscriptionProvider> {
+    match service {
+        Service::OpenAI => Box::new(OpenAiProvider),
+        Service::Replicate => Box::new(ReplicateProvider),
+        Service::ElevenLabs => Box::new(ElevenLabsProvider),
+        Service::Deepgram => Box::new(DeepgramProvider),
+        Service::Local => unreachable!("Local is transcribed via transcribe_local, not a TranscriptionProvider"),
+    }
+}
+
+/// Outcome of a single attempt inside `with_retries`: either the final
+/// success value or a transient failure worth retrying. Anything else should
+/// be returned as a fatal `Err` straight out of the attempt closure.
+enum AttemptOutcome<T> {
+    Success(T),
+    Retry(String),
+}
+
+/// Shared retry/backoff loop used by every `TranscriptionProvider`: runs
+/// `attempt` up to `config.retries` additional times with exponential
+/// backoff between tries, stopping early on a fatal `Err` or a `Success`.
+/// Generic over the success payload so Replicate's "create prediction" step
+/// (which only needs the poll URL) and the other providers' full
+/// `Transcription` can share the same loop.
+///
+/// `provider` is the stable metrics label (`"openai"`, `"replicate"`, ...);
+/// `label` is the human-readable description used in logs.
+async fn with_retries<T, F, Fut>(config: &Config, provider: &str, label: &str, mut attempt: F) -> Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<AttemptOutcome<T>>>,
+{
+    let started = std::time::Instant::now();
+    metrics::record_request(provider);
+    for n in 0..=config.retries {
+        if n > 0 {
+            let delay = Duration::from_secs(2u64.pow(n - 1));
+            info!("Retrying {} (attempt {}) after {:?}", label, n + 1, delay);
+            sleep(delay).await;
+        }
+        match attempt().await {
+            Ok(AttemptOutcome::Success(value)) => {
+                metrics::record_success(provider);
+                metrics::record_latency(provider, started.elapsed().as_secs_f64());
+                return Ok(value);
+            }
+            Ok(AttemptOutcome::Retry(reason)) => {
+                metrics::record_retry(provider);
+                warn!("{} failed ({}), retrying...", label, reason);
+            }
+            Err(e) => {
+                metrics::record_failure(provider);
+                metrics::record_latency(provider, started.elapsed().as_secs_f64());
+                return Err(e);
+            }
+        }
+    }
+    metrics::record_failure(provider);
+    metrics::record_latency(provider, started.elapsed().as_secs_f64());
+    Err(anyhow!("{} failed after {} retries.", label, config.retries))
+}
+
+/// Classifies a completed HTTP request into the `AttemptOutcome` the retry
+/// loop expects: success runs `parse_success` over the response, 429/5xx
+/// (or a timeout/connect error) is retried, anything else is fatal.
+async fn classify_response<T, F, Fut>(
+    response: reqwest::Result<Response>,
+    label: &str,
+    parse_success: F,
+) -> Result<AttemptOutcome<T>>
+where
+    F: FnOnce(Response) -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    match response {
+        Ok(resp) => {
+            let status = resp.status();
+            debug!("{} status: {}", label, status);
+            if status.is_success() {
+                Ok(AttemptOutcome::Success(parse_success(resp).await?))
+            } else {
+                let body = if status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error() {
+                    status.to_string()
+                } else {
+                    resp.text().await.unwrap_or_else(|_| "Failed to read error body".into())
+                };
+                let err = TranscribeError::from_status(status, body);
+                // `from_status` also files unmapped statuses (e.g. a 404)
+                // under `ServerError`; only a real 429/5xx is retried.
+                let transient = status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error();
+                if err.is_retryable() && transient {
+                    Ok(AttemptOutcome::Retry(err.to_string()))
+                } else {
+                    warn!("{} failed fatally: {}", label, err);
+                    Err(err.into())
+                }
+            }
+        }
+        Err(e) => {
+            let err = if e.is_timeout() || e.is_connect() {
+                TranscribeError::Timeout(e.to_string())
+            } else if e.is_decode() {
+                TranscribeError::Parse(e.to_string())
+            } else {
+                TranscribeError::Request(e.to_string())
+            };
+            if err.is_retryable() {
+                Ok(AttemptOutcome::Retry(err.to_string()))
+            } else {
+                warn!("{} request failed fatally: {}", label, err);
+                Err(err.into())
+            }
+        }
+    }
+}
 
 // --- Replicate ---
 
@@ -60,197 +282,185 @@ struct ReplicatePollResponse {
     error: Option<String>,
 }
 
-pub async fn transcribe_replicate(config: &Config, audio_path: &Path) -> Result<String> {
-    let client = Client::new();
-    let api_token = &config.api_key;
-
-    // 1. Read audio and convert to data URI
-    let audio_bytes = tokio::fs::read(audio_path)
-        .await
-        .context("Failed to read audio file")?;
-    let audio_base64 = BASE64_STANDARD.encode(&audio_bytes);
-    // Assuming WAV format from input
-    let audio_data_uri = format!("data:audio/wav;base64,{}", audio_base64);
-    debug!(
-        "Audio size: {} bytes, Data URI prefix: data:audio/wav;base64,...",
-        audio_bytes.len()
-    );
-
-    // 2. Create Prediction
-    let create_payload = ReplicateCreatePayload {
-        version: REPLICATE_MODEL_VERSION,
-        input: ReplicateInput {
-            audio: audio_data_uri,
-            batch_size: 64,
-        },
-    };
-
-    let mut prediction_url: Option<String> = None;
-    for attempt in 0..=config.retries {
-        if attempt > 0 {
-            let delay = Duration::from_secs(2u64.pow(attempt - 1));
-            info!(
-                "Retrying Replicate create prediction (attempt {}) after {:?}",
-                attempt + 1,
-                delay
-            );
-            sleep(delay).await;
-        }
+pub struct ReplicateProvider;
+
+#[async_trait]
+impl TranscriptionProvider for ReplicateProvider {
+    async fn transcribe(&self, config: &Config, audio_path: &Path) -> Result<Transcription> {
+        let client = Client::new();
+        let api_token = &config.api_key;
+
+        // 1. Read audio and convert to data URI
+        let audio_bytes = tokio::fs::read(audio_path).await.map_err(|e| {
+            TranscribeError::BadInput(format!("Failed to read audio file {}: {}", audio_path.display(), e))
+        })?;
+        let audio_base64 = BASE64_STANDARD.encode(&audio_bytes);
+        let mime_type = config.audio_format.mime_type();
+        let audio_data_uri = format!("data:{};base64,{}", mime_type, audio_base64);
+        debug!(
+            "Audio size: {} bytes, Data URI prefix: data:{};base64,...",
+            audio_bytes.len(),
+            mime_type
+        );
 
-        let response = client
-            .post(REPLICATE_API_URL)
-            .bearer_auth(api_token)
-            .json(&create_payload)
-            .timeout(Duration::from_secs(30))
-            .send()
-            .await;
-
-        match response {
-            Ok(resp) => {
-                let status = resp.status();
-                debug!("Replicate Create Status: {}", status);
-                if status.is_success() {
-                    let create_resp = resp.json::<ReplicateCreateResponse>().await?;
-                    prediction_url = Some(create_resp.urls.get);
-                    info!(
-                        "Replicate prediction created: ID={}, URL={}",
-                        create_resp.id,
-                        prediction_url.as_ref().unwrap()
-                    );
-                    break;
-                } else if status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error() {
-                    warn!("Replicate create failed ({}), retrying...", status);
-                    continue;
-                } else {
-                    let error_text = resp
-                        .text()
-                        .await
-                        .unwrap_or_else(|_| "Failed to read error body".into());
-                    return Err(anyhow!(
-                        "Replicate create failed ({}): {}",
-                        status,
-                        error_text
-                    ));
-                }
-            }
-            Err(e) => {
-                if e.is_timeout() && attempt < config.retries {
-                    warn!("Replicate create timed out, retrying...");
-                    continue;
-                } else if e.is_connect() && attempt < config.retries {
-                    warn!("Replicate create connection error, retrying...");
-                    continue;
-                }
-                return Err(anyhow!("Replicate create request failed: {}", e).context(e));
+        // 2. Create Prediction
+        let create_payload = ReplicateCreatePayload {
+            version: REPLICATE_MODEL_VERSION,
+            input: ReplicateInput {
+                audio: audio_data_uri,
+                batch_size: 64,
+            },
+        };
+
+        let get_url = with_retries(config, "replicate", "Replicate create prediction", || async {
+            let response = client
+                .post(REPLICATE_API_URL)
+                .bearer_auth(api_token)
+                .json(&create_payload)
+                .timeout(Duration::from_secs(30))
+                .send()
+                .await;
+            classify_response(response, "Replicate create prediction", |resp| async move {
+                let create_resp = resp
+                    .json::<ReplicateCreateResponse>()
+                    .await
+                    .map_err(|e| TranscribeError::Parse(e.to_string()))?;
+                info!(
+                    "Replicate prediction created: ID={}, URL={}",
+                    create_resp.id, create_resp.urls.get
+                );
+                Ok(create_resp.urls.get)
+            })
+            .await
+        })
+        .await?;
+
+        // 3. Poll for Result. This loop runs until a terminal prediction
+        // status or REPLICATE_POLL_TIMEOUT, which is a different shape of
+        // retry than config.retries governs, so it stays bespoke rather
+        // than going through with_retries.
+        let start_time = tokio::time::Instant::now();
+        loop {
+            if start_time.elapsed() > REPLICATE_POLL_TIMEOUT {
+                metrics::record_failure("replicate");
+                return Err(anyhow!(
+                    "Replicate polling timed out after {:?}",
+                    REPLICATE_POLL_TIMEOUT
+                ));
             }
-        }
-    } // End create retry loop
-
-    let get_url = prediction_url.context("Failed to create Replicate prediction after retries.")?;
-
-    // 3. Poll for Result
-    let start_time = tokio::time::Instant::now();
-    loop {
-        if start_time.elapsed() > REPLICATE_POLL_TIMEOUT {
-            return Err(anyhow!(
-                "Replicate polling timed out after {:?}",
-                REPLICATE_POLL_TIMEOUT
-            ));
-        }
 
-        info!(
-            "Polling Replicate status ({:?} elapsed)...",
-            start_time.elapsed()
-        );
-        let poll_response = client
-            .get(&get_url)
-            .bearer_auth(api_token)
-            .timeout(Duration::from_secs(15))
-            .send()
-            .await;
-
-        match poll_response {
-            Ok(resp) => {
-                let status = resp.status();
-                debug!("Replicate Poll Status: {}", status);
-                if status.is_success() {
-                    let prediction = resp.json::<ReplicatePollResponse>().await?;
-                    match prediction.status.as_str() {
-                        "succeeded" => {
-                            info!("Replicate prediction succeeded.");
-                            // Extract transcription text (handle various possible output formats)
-                            return extract_replicate_transcription(prediction.output);
-                        }
-                        "failed" => {
-                            return Err(anyhow!(
-                                "Replicate prediction failed: {}",
-                                prediction.error.unwrap_or_else(|| "Unknown error".into())
-                            ));
-                        }
-                        "canceled" => {
-                            return Err(anyhow!("Replicate prediction canceled."));
-                        }
-                        "starting" | "processing" => {
-                            // Continue polling
-                        }
-                        unknown => {
-                            warn!("Unknown Replicate status: {}", unknown);
+            info!("Polling Replicate status ({:?} elapsed)...", start_time.elapsed());
+            let poll_response = client
+                .get(&get_url)
+                .bearer_auth(api_token)
+                .timeout(Duration::from_secs(15))
+                .send()
+                .await;
+
+            match poll_response {
+                Ok(resp) => {
+                    let status = resp.status();
+                    debug!("Replicate Poll Status: {}", status);
+                    if status.is_success() {
+                        let prediction = resp.json::<ReplicatePollResponse>().await.map_err(|e| {
+                            metrics::record_failure("replicate");
+                            TranscribeError::Parse(e.to_string())
+                        })?;
+                        match prediction.status.as_str() {
+                            "succeeded" => {
+                                info!("Replicate prediction succeeded.");
+                                metrics::record_replicate_poll_seconds(start_time.elapsed().as_secs_f64());
+                                // Extract transcription text (handle various possible output formats)
+                                return extract_replicate_transcription(prediction.output);
+                            }
+                            "failed" => {
+                                metrics::record_failure("replicate");
+                                return Err(anyhow!(
+                                    "Replicate prediction failed: {}",
+                                    prediction.error.unwrap_or_else(|| "Unknown error".into())
+                                ));
+                            }
+                            "canceled" => {
+                                metrics::record_failure("replicate");
+                                return Err(anyhow!("Replicate prediction canceled."));
+                            }
+                            "starting" | "processing" => {
+                                // Continue polling
+                            }
+                            unknown => {
+                                warn!("Unknown Replicate status: {}", unknown);
+                            }
                         }
+                    } else if status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error() {
+                        warn!("Replicate poll received status {}, waiting...", status);
+                        metrics::record_retry("replicate");
+                        sleep(REPLICATE_POLL_INTERVAL * 2).await; // Wait longer
+                        continue;
+                    } else {
+                        let error_text = resp
+                            .text()
+                            .await
+                            .unwrap_or_else(|_| "Failed to read error body".into());
+                        metrics::record_failure("replicate");
+                        return Err(anyhow!("Replicate poll failed ({}): {}", status, error_text));
                     }
-                } else if status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error() {
-                    warn!("Replicate poll received status {}, waiting...", status);
-                    sleep(REPLICATE_POLL_INTERVAL * 2).await; // Wait longer
-                    continue;
-                } else {
-                    let error_text = resp
-                        .text()
-                        .await
-                        .unwrap_or_else(|_| "Failed to read error body".into());
-                    return Err(anyhow!(
-                        "Replicate poll failed ({}): {}",
-                        status,
-                        error_text
-                    ));
                 }
-            }
-            Err(e) => {
-                if e.is_timeout() {
-                    warn!("Replicate poll timed out, continuing poll...");
-                } else {
-                    warn!("Replicate poll request error: {}, continuing poll...", e);
-                    sleep(REPLICATE_POLL_INTERVAL * 2).await; // Wait longer after error
+                Err(e) => {
+                    metrics::record_retry("replicate");
+                    if e.is_timeout() {
+                        warn!("Replicate poll timed out, continuing poll...");
+                    } else {
+                        warn!("Replicate poll request error: {}, continuing poll...", e);
+                        sleep(REPLICATE_POLL_INTERVAL * 2).await; // Wait longer after error
+                    }
                 }
-            }
-        } // End match poll_response
+            } // End match poll_response
 
-        sleep(REPLICATE_POLL_INTERVAL).await;
-    } // End poll loop
+            sleep(REPLICATE_POLL_INTERVAL).await;
+        } // End poll loop
+    }
 }
 
-fn extract_replicate_transcription(output: Option<Value>) -> Result<String> {
+fn extract_replicate_transcription(output: Option<Value>) -> Result<Transcription> {
     match output {
         Some(Value::Object(map)) => {
-            // Look for common keys
+            // Prefer segments when present, since they carry start/end timing
+            // the plain 'transcription'/'text' keys don't.
+            if let Some(Value::Array(segments)) = map.get("segments") {
+                let segments: Vec<Segment> = segments
+                    .iter()
+                    .filter_map(|seg| {
+                        let text = seg.get("text")?.as_str()?.trim().to_string();
+                        let start = seg.get("start").and_then(Value::as_f64).unwrap_or(0.0);
+                        let end = seg.get("end").and_then(Value::as_f64).unwrap_or(0.0);
+                        Some(Segment { start, end, text })
+                    })
+                    .collect();
+                if !segments.is_empty() {
+                    let text = segments
+                        .iter()
+                        .map(|seg| seg.text.as_str())
+                        .collect::<Vec<&str>>()
+                        .join(" ")
+                        .trim()
+                        .to_string();
+                    return Ok(Transcription { text, segments });
+                }
+            }
             if let Some(text) = map.get("transcription").and_then(|v| v.as_str()) {
-                Ok(text.trim().to_string())
+                Ok(Transcription::from_text(text.trim().to_string()))
             } else if let Some(text) = map.get("text").and_then(|v| v.as_str()) {
-                Ok(text.trim().to_string())
-            } else if let Some(Value::Array(segments)) = map.get("segments") {
-                // Assemble from segments
-                let combined = segments
-                    .iter()
-                    .filter_map(|seg| seg.get("text").and_then(|t| t.as_str()))
-                    .map(|s| s.trim())
-                    .collect::<Vec<&str>>()
-                    .join(" ");
-                Ok(combined.trim().to_string())
+                Ok(Transcription::from_text(text.trim().to_string()))
             } else {
-                Err(anyhow!("Could not find 'transcription', 'text', or 'segments' in Replicate output object: {:?}", map))
+                Err(anyhow!(
+                    "Could not find 'transcription', 'text', or 'segments' in Replicate output object: {:?}",
+                    map
+                ))
             }
         }
         Some(Value::String(s)) => {
             // Sometimes the output is just the string
-            Ok(s.trim().to_string())
+            Ok(Transcription::from_text(s.trim().to_string()))
         }
         _ => Err(anyhow!(
             "Unexpected or missing output format from Replicate: {:?}",
@@ -264,92 +474,222 @@ fn extract_replicate_transcription(output: Option<Value>) -> Result<String> {
 #[derive(Deserialize, Debug)]
 struct ElevenLabsResponse {
     text: String,
+    /// Word-level timing ElevenLabs includes by default; absent (or empty)
+    /// if the response ever omits it, so `Transcription::segments` just ends
+    /// up empty rather than erroring.
+    #[serde(default)]
+    words: Vec<ElevenLabsWord>,
+}
+
+#[derive(Deserialize, Debug)]
+struct ElevenLabsWord {
+    text: String,
+    start: f64,
+    end: f64,
+}
+
+pub struct ElevenLabsProvider;
+
+#[async_trait]
+impl TranscriptionProvider for ElevenLabsProvider {
+    async fn transcribe(&self, config: &Config, audio_path: &Path) -> Result<Transcription> {
+        let client = Client::new();
+        let api_key = &config.api_key;
+
+        with_retries(config, "elevenlabs", "ElevenLabs API call", || async {
+            let file = File::open(audio_path).await.map_err(|e| {
+                TranscribeError::BadInput(format!("Failed to open audio file {}: {}", audio_path.display(), e))
+            })?;
+            let stream = FramedRead::new(file, BytesCodec::new());
+            let file_body = Body::wrap_stream(stream);
+
+            let audio_part = multipart::Part::stream(file_body)
+                .file_name(audio_path.file_name().map_or_else(
+                    || format!("audio.{}", config.audio_format.file_extension()),
+                    |n| n.to_string_lossy().into_owned(),
+                ))
+                .mime_str(config.audio_format.mime_type())?;
+
+            let form = multipart::Form::new()
+                .text("model_id", ELEVENLABS_MODEL.to_string())
+                .part("file", audio_part);
+
+            let response = client
+                .post(ELEVENLABS_API_URL)
+                .header("xi-api-key", api_key)
+                .multipart(form)
+                .timeout(Duration::from_secs(45)) // Longer timeout for upload/processing
+                .send()
+                .await;
+
+            classify_response(response, "ElevenLabs API call", |resp| async move {
+                let result = resp
+                    .json::<ElevenLabsResponse>()
+                    .await
+                    .map_err(|e| TranscribeError::Parse(e.to_string()))?;
+                let segments = result
+                    .words
+                    .into_iter()
+                    .map(|word| Segment { start: word.start, end: word.end, text: word.text })
+                    .collect();
+                Ok(Transcription { text: result.text.trim().to_string(), segments })
+            })
+            .await
+        })
+        .await
+    }
 }
 
-pub async fn transcribe_elevenlabs(config: &Config, audio_path: &Path) -> Result<String> {
-    let client = Client::new();
-    let api_key = &config.api_key;
+// --- Deepgram ---
 
-    // Prepare multipart form data
+const DEEPGRAM_API_URL: &str = "https://api.deepgram.com/v1/listen";
+const DEEPGRAM_MODEL: &str = "nova-2";
 
-    for attempt in 0..=config.retries {
-        if attempt > 0 {
-            let delay = Duration::from_secs(2u64.pow(attempt - 1));
-            info!(
-                "Retrying ElevenLabs API call (attempt {}) after {:?}",
-                attempt + 1,
-                delay
-            );
-            sleep(delay).await;
-        }
-        let file = File::open(audio_path)
+#[derive(Deserialize, Debug)]
+struct DeepgramResponse {
+    results: DeepgramResults,
+}
+
+#[derive(Deserialize, Debug)]
+struct DeepgramResults {
+    channels: Vec<DeepgramChannel>,
+}
+
+#[derive(Deserialize, Debug)]
+struct DeepgramChannel {
+    alternatives: Vec<DeepgramAlternative>,
+}
+
+#[derive(Deserialize, Debug)]
+struct DeepgramAlternative {
+    transcript: String,
+}
+
+pub struct DeepgramProvider;
+
+#[async_trait]
+impl TranscriptionProvider for DeepgramProvider {
+    async fn transcribe(&self, config: &Config, audio_path: &Path) -> Result<Transcription> {
+        let client = Client::new();
+        let api_key = &config.api_key;
+
+        with_retries(config, "deepgram", "Deepgram API call", || async {
+            let file = File::open(audio_path).await.map_err(|e| {
+                TranscribeError::BadInput(format!("Failed to open audio file {}: {}", audio_path.display(), e))
+            })?;
+            let stream = FramedRead::new(file, BytesCodec::new());
+            let file_body = Body::wrap_stream(stream);
+
+            let response = client
+                .post(DEEPGRAM_API_URL)
+                .header("Authorization", format!("Token {}", api_key))
+                .header("Content-Type", config.audio_format.mime_type())
+                .query(&[("model", DEEPGRAM_MODEL), ("smart_format", "true"), ("punctuate", "true")])
+                .body(file_body)
+                .timeout(Duration::from_secs(45))
+                .send()
+                .await;
+
+            classify_response(response, "Deepgram API call", |resp| async move {
+                let result = resp
+                    .json::<DeepgramResponse>()
+                    .await
+                    .map_err(|e| TranscribeError::Parse(e.to_string()))?;
+                result
+                    .results
+                    .channels
+                    .first()
+                    .and_then(|channel| channel.alternatives.first())
+                    .map(|alternative| Transcription::from_text(alternative.transcript.trim().to_string()))
+                    .ok_or_else(|| TranscribeError::Parse("Deepgram response had no channels/alternatives".to_string()).into())
+            })
             .await
-            .context("Failed to open audio file for ElevenLabs upload")?;
-        let stream = FramedRead::new(file, BytesCodec::new());
-        let file_body = Body::wrap_stream(stream);
-
-        let audio_part = multipart::Part::stream(file_body)
-            .file_name(
-                audio_path
-                    .file_name()
-                    .map_or("audio.wav".into(), |n| n.to_string_lossy().into_owned()),
-            )
-            .mime_str("audio/wav")?;
-
-        let form = multipart::Form::new()
-            .text("model_id", ELEVENLABS_MODEL.to_string())
-            .part("file", audio_part);
-
-        let response = client
-            .post(ELEVENLABS_API_URL)
-            .header("xi-api-key", api_key)
-            .multipart(form) // Send the potentially rebuilt/cloned form
-            .timeout(Duration::from_secs(45)) // Longer timeout for upload/processing
-            .send()
-            .await;
-
-        match response {
-            Ok(resp) => {
-                let status = resp.status();
-                debug!("ElevenLabs API Status Code: {}", status);
-                if status.is_success() {
-                    let result = resp.json::<ElevenLabsResponse>().await?;
-                    return Ok(result.text.trim().to_string());
-                } else if status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error() {
-                    warn!("ElevenLabs API failed ({}), retrying...", status);
-                    // No need to reset file pointer manually, form rebuilt on retry
-                    continue;
-                } else {
-                    let error_text = resp
-                        .text()
-                        .await
-                        .unwrap_or_else(|_| "Failed to read error body".into());
-                    return Err(anyhow!(
-                        "ElevenLabs API call failed ({}): {}",
-                        status,
-                        error_text
-                    ));
-                }
-            }
-            Err(e) => {
-                if e.is_timeout() && attempt < config.retries {
-                    warn!("ElevenLabs request timed out, retrying...");
-                    // Rebuild form on next iteration
-                    continue;
-                } else if e.is_connect() && attempt < config.retries {
-                    warn!("ElevenLabs connection error, retrying...");
-                    // Rebuild form on next iteration
-                    continue;
-                }
-                return Err(anyhow!("ElevenLabs API request failed: {}", e).context(e));
+        })
+        .await
+    }
+}
+
+// --- Local (whisper.cpp via whisper-rs) ---
+//
+// This backend is whisper-rs/whisper.cpp, the same one `Service::Local`
+// has used since it was introduced — not Candle, and this crate has no
+// `candle` dependency. whisper-rs doesn't expose a way to reset and reuse
+// a decoder's KV-cache buffers across calls, so what's below is limited
+// to caching the loaded `WhisperContext` itself.
+
+/// Cached, loaded model, keyed by the path it was loaded from. Reusing it
+/// across recordings avoids re-reading the (often multi-gigabyte) model
+/// file from disk on every utterance. It does *not* avoid per-call decoder
+/// setup: `transcribe_local` still calls `ctx.create_state()` fresh on
+/// every invocation, since `WhisperState` borrows from `WhisperContext` and
+/// reusing one across calls would mean storing a self-referential struct
+/// here. That setup is cheap relative to the model load this cache does
+/// avoid, so it's left as-is rather than worked around with `unsafe`.
+static LOCAL_MODEL: Lazy<AsyncMutex<Option<(PathBuf, Arc<whisper_rs::WhisperContext>)>>> =
+    Lazy::new(|| AsyncMutex::new(None));
+
+/// Transcribes already-captured 16 kHz mono samples through a bundled
+/// whisper.cpp model, entirely offline. Unlike a `TranscriptionProvider`
+/// this takes `&[f32]` directly rather than a path, so the streaming/local
+/// caller can skip the temp-file round-trip.
+pub async fn transcribe_local(config: &Config, samples: &[f32]) -> Result<String> {
+    let model_path = config
+        .model
+        .clone()
+        .ok_or_else(|| anyhow!("--model <path> is required when using the local service"))?;
+    let language = config.language.clone();
+    let samples = samples.to_vec();
+
+    // whisper-rs calls into libwhisper.cpp synchronously; run it on a
+    // blocking thread so it doesn't stall the tokio runtime.
+    tokio::task::spawn_blocking(move || -> Result<String> {
+        use whisper_rs::{FullParams, SamplingStrategy, WhisperContext, WhisperContextParameters};
+
+        let ctx = {
+            let mut cached = LOCAL_MODEL.blocking_lock();
+            let needs_reload = !matches!(cached.as_ref(), Some((cached_path, _)) if *cached_path == model_path);
+            if needs_reload {
+                let model_path_str = model_path
+                    .to_str()
+                    .ok_or_else(|| anyhow!("Model path '{}' is not valid UTF-8", model_path.display()))?;
+                let ctx = WhisperContext::new_with_params(model_path_str, WhisperContextParameters::default())
+                    .with_context(|| format!("Failed to load whisper model from {}", model_path.display()))?;
+                debug!("Loaded local whisper model from {}", model_path.display());
+                *cached = Some((model_path.clone(), Arc::new(ctx)));
             }
+            cached.as_ref().unwrap().1.clone()
+        };
+        let mut state = ctx
+            .create_state()
+            .context("Failed to create whisper inference state")?;
+
+        let mut params = FullParams::new(SamplingStrategy::Greedy { best_of: 1 });
+        params.set_print_progress(false);
+        params.set_print_special(false);
+        params.set_print_realtime(false);
+        params.set_print_timestamps(false);
+        if let Some(lang) = language.as_deref() {
+            params.set_language(Some(lang));
         }
-    } // End retry loop
 
-    Err(anyhow!(
-        "ElevenLabs API call failed after {} retries.",
-        config.retries
-    ))
+        state
+            .full(params, &samples)
+            .context("Local whisper inference failed")?;
+
+        let num_segments = state
+            .full_n_segments()
+            .context("Failed to read whisper segment count")?;
+        let mut text = String::new();
+        for i in 0..num_segments {
+            let segment = state
+                .full_get_segment_text(i)
+                .context("Failed to read whisper segment text")?;
+            text.push_str(&segment);
+        }
+        Ok(text.trim().to_string())
+    })
+    .await
+    .context("Local transcription task panicked")?
 }
 
 // --- OpenAI ---
@@ -357,95 +697,135 @@ pub async fn transcribe_elevenlabs(config: &Config, audio_path: &Path) -> Result
 #[derive(Deserialize, Debug)]
 struct OpenAIResponse {
     text: String,
+    /// Only populated when the request asks for `verbose_json` with
+    /// `timestamp_granularities[]=segment`, which is what we always send.
+    #[serde(default)]
+    segments: Vec<OpenAISegment>,
 }
 
-pub async fn transcribe_openai(config: &Config, audio_path: &Path) -> Result<String> {
-    let client = Client::new();
-    let api_key = &config.api_key;
+#[derive(Deserialize, Debug)]
+struct OpenAISegment {
+    start: f64,
+    end: f64,
+    text: String,
+}
 
-    if api_key.is_empty() {
-        return Err(anyhow!(
-            "OpenAI API key is missing. Please provide it via --api-key or OPENAI_API_KEY env var."
-        ));
-    }
+pub struct OpenAiProvider;
 
-    for attempt in 0..=config.retries {
-        if attempt > 0 {
-            let delay = Duration::from_secs(2u64.pow(attempt - 1));
-            info!(
-                "Retrying OpenAI API call (attempt {}) after {:?}" ,
-                attempt + 1,
-                delay
-            );
-            sleep(delay).await;
-        }
+#[async_trait]
+impl TranscriptionProvider for OpenAiProvider {
+    async fn transcribe(&self, config: &Config, audio_path: &Path) -> Result<Transcription> {
+        let client = Client::new();
+        let api_key = &config.api_key;
 
-        // Re-open file and prepare form data inside the loop for retries
-        let file = File::open(audio_path)
-            .await
-            .context("Failed to open audio file for OpenAI upload")?;
-        let stream = FramedRead::new(file, BytesCodec::new());
-        let file_body = Body::wrap_stream(stream);
-
-        let audio_part = multipart::Part::stream(file_body)
-            .file_name(
-                audio_path
-                    .file_name()
-                    .map_or("audio.wav".into(), |n| n.to_string_lossy().into_owned()),
+        if api_key.is_empty() {
+            return Err(TranscribeError::Auth(
+                "OpenAI API key is missing. Please provide it via --api-key or OPENAI_API_KEY env var.".to_string(),
             )
-            .mime_str("audio/wav")?; // OpenAI supports various formats, wav is safe
-
-        let form = multipart::Form::new()
-            .text("model", OPENAI_MODEL.to_string())
-            .text("prompt", "The following recording is made by a technical user who knows computer science and software engineering well.")
-            .part("file", audio_part);
-
-        let response = client
-            .post(OPENAI_API_URL)
-            .bearer_auth(api_key)
-            .multipart(form)
-            .timeout(Duration::from_secs(60)) // Increased timeout for potential processing
-            .send()
-            .await;
-
-        match response {
-            Ok(resp) => {
-                let status = resp.status();
-                debug!("OpenAI API Status Code: {}", status);
-                if status.is_success() {
-                    let result = resp.json::<OpenAIResponse>().await.context("Failed to parse OpenAI JSON response")?;
-                    info!("OpenAI transcription successful.");
-                    return Ok(result.text.trim().to_string());
-                } else if status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error() {
-                    warn!("OpenAI API failed ({}), retrying...", status);
-                    continue; // Retry
-                } else {
-                    let error_text = resp
-                        .text()
-                        .await
-                        .unwrap_or_else(|_| "Failed to read error body".into());
-                    return Err(anyhow!(
-                        "OpenAI API call failed ({}): {}",
-                        status,
-                        error_text
-                    ));
-                }
-            }
-            Err(e) => {
-                // Retry on timeout or connection errors
-                if (e.is_timeout() || e.is_connect()) && attempt < config.retries {
-                    warn!("OpenAI request error ({}), retrying...", e);
-                    continue; // Retry
-                } else {
-                    // For other errors or if retries exhausted, return the error
-                    return Err(anyhow!("OpenAI API request failed: {}", e).context(e));
-                }
-            }
+            .into());
         }
-    } // End retry loop
 
-    Err(anyhow!(
-        "OpenAI API call failed after {} retries.",
-        config.retries
-    ))
+        let url = config.base_url.as_deref().unwrap_or(OPENAI_API_URL);
+        let model = config.model_name.as_deref().unwrap_or(OPENAI_MODEL);
+
+        with_retries(config, "openai", "OpenAI API call", || async {
+            // Re-open file and prepare form data inside the closure so it's
+            // rebuilt fresh for each retry.
+            let file = File::open(audio_path).await.map_err(|e| {
+                TranscribeError::BadInput(format!("Failed to open audio file {}: {}", audio_path.display(), e))
+            })?;
+            let stream = FramedRead::new(file, BytesCodec::new());
+            let file_body = Body::wrap_stream(stream);
+
+            let audio_part = multipart::Part::stream(file_body)
+                .file_name(audio_path.file_name().map_or_else(
+                    || format!("audio.{}", config.audio_format.file_extension()),
+                    |n| n.to_string_lossy().into_owned(),
+                ))
+                .mime_str(config.audio_format.mime_type())?; // OpenAI supports wav/flac/etc.
+
+            let form = multipart::Form::new()
+                .text("model", model.to_string())
+                .text("prompt", "The following recording is made by a technical user who knows computer science and software engineering well.")
+                .text("response_format", "verbose_json")
+                .text("timestamp_granularities[]", "segment")
+                .part("file", audio_part);
+
+            let response = client
+                .post(url)
+                .bearer_auth(api_key)
+                .multipart(form)
+                .timeout(Duration::from_secs(60)) // Increased timeout for potential processing
+                .send()
+                .await;
+
+            classify_response(response, "OpenAI API call", |resp| async move {
+                let result = resp
+                    .json::<OpenAIResponse>()
+                    .await
+                    .map_err(|e| TranscribeError::Parse(e.to_string()))?;
+                info!("OpenAI transcription successful.");
+                let segments = result
+                    .segments
+                    .into_iter()
+                    .map(|seg| Segment { start: seg.start, end: seg.end, text: seg.text.trim().to_string() })
+                    .collect();
+                Ok(Transcription { text: result.text.trim().to_string(), segments })
+            })
+            .await
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_status_maps_known_codes_to_their_variant() {
+        assert!(matches!(
+            TranscribeError::from_status(StatusCode::UNAUTHORIZED, "x".to_string()),
+            TranscribeError::Auth(_)
+        ));
+        assert!(matches!(
+            TranscribeError::from_status(StatusCode::FORBIDDEN, "x".to_string()),
+            TranscribeError::Auth(_)
+        ));
+        assert!(matches!(
+            TranscribeError::from_status(StatusCode::BAD_REQUEST, "x".to_string()),
+            TranscribeError::BadInput(_)
+        ));
+        assert!(matches!(
+            TranscribeError::from_status(StatusCode::TOO_MANY_REQUESTS, "x".to_string()),
+            TranscribeError::RateLimited(_)
+        ));
+        assert!(matches!(
+            TranscribeError::from_status(StatusCode::INTERNAL_SERVER_ERROR, "x".to_string()),
+            TranscribeError::ServerError(_)
+        ));
+        assert!(matches!(
+            TranscribeError::from_status(StatusCode::BAD_GATEWAY, "x".to_string()),
+            TranscribeError::ServerError(_)
+        ));
+        // Anything else (e.g. a 404, or a redirect code a provider shouldn't
+        // send here) falls back to ServerError rather than panicking on an
+        // unmapped status.
+        assert!(matches!(
+            TranscribeError::from_status(StatusCode::NOT_FOUND, "x".to_string()),
+            TranscribeError::ServerError(_)
+        ));
+    }
+
+    #[test]
+    fn is_retryable_only_for_rate_limit_server_error_and_timeout() {
+        assert!(TranscribeError::RateLimited("x".to_string()).is_retryable());
+        assert!(TranscribeError::ServerError("x".to_string()).is_retryable());
+        assert!(TranscribeError::Timeout("x".to_string()).is_retryable());
+
+        assert!(!TranscribeError::Auth("x".to_string()).is_retryable());
+        assert!(!TranscribeError::BadInput("x".to_string()).is_retryable());
+        assert!(!TranscribeError::Parse("x".to_string()).is_retryable());
+        assert!(!TranscribeError::Request("x".to_string()).is_retryable());
+    }
 }
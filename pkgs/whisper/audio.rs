@@ -1,3 +1,4 @@
+use crate::config::AudioFormat;
 use anyhow::{anyhow, Context, Result};
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
 use cpal::{SampleRate, Stream, StreamConfig, SupportedStreamConfigRange};
@@ -10,6 +11,246 @@ use std::time::Duration;
 pub const MAX_SAMPLE_RATE: u32 = 44100;
 pub const MAX_CHANNELS: u16 = 2;
 
+/// Target length and overlap of each window in `--stream` mode.
+const STREAM_WINDOW_SECONDS: f32 = 5.0;
+const STREAM_OVERLAP_SECONDS: f32 = 0.5;
+/// Ring buffer capacity, in seconds of audio, backing the streaming
+/// capture's producer/consumer handoff.
+const STREAM_RING_SECONDS: usize = 10;
+
+/// One WAV-encoded window of `--stream` audio, already downmixed/resampled
+/// to the STT target format. `sequence` is monotonically increasing so a
+/// consumer can order chunks that arrive out of order.
+pub struct StreamChunk {
+    pub sequence: usize,
+    pub wav_bytes: Vec<u8>,
+}
+
+/// One enumerated capture device, with the format ranges it advertises so
+/// callers can present choices and check compatibility before opening it.
+#[derive(Debug, Clone)]
+pub struct DeviceInfo {
+    pub name: String,
+    pub supported_configs: Vec<SupportedStreamConfigRange>,
+}
+
+impl DeviceInfo {
+    /// Whether at least one of this device's supported configs fits within
+    /// `MAX_CHANNELS`/`MAX_SAMPLE_RATE`.
+    pub fn satisfies_limits(&self) -> bool {
+        self.supported_configs
+            .iter()
+            .any(|c| c.channels() <= MAX_CHANNELS && c.min_sample_rate().0 <= MAX_SAMPLE_RATE)
+    }
+}
+
+/// Lists every input-capable device on the default host, e.g. for a
+/// `--list-devices` flag or a picker UI.
+pub fn list_input_devices() -> Result<Vec<DeviceInfo>> {
+    let host = cpal::default_host();
+    host.input_devices()
+        .context("Failed to enumerate input devices")?
+        .map(|device| {
+            let name = device.name().context("Failed to read device name")?;
+            let supported_configs = device.supported_input_configs()?.collect();
+            Ok(DeviceInfo {
+                name,
+                supported_configs,
+            })
+        })
+        .collect()
+}
+
+/// Resolves `--device` to a concrete cpal device. Falls back to the host's
+/// default input device (logging a warning) if `name` is `None` or doesn't
+/// match any enumerated device.
+fn resolve_input_device(host: &cpal::Host, name: Option<&str>) -> Result<cpal::Device> {
+    if let Some(name) = name {
+        let mut devices = host.input_devices().context("Failed to enumerate input devices")?;
+        if let Some(device) = devices.find(|d| d.name().map(|n| n == name).unwrap_or(false)) {
+            return Ok(device);
+        }
+        warn!(
+            "Requested input device '{}' not found, falling back to default.",
+            name
+        );
+    }
+    host.default_input_device()
+        .context("No default input device available")
+}
+
+/// Default for how many times above the calibrated noise floor a chunk's
+/// RMS energy must rise/stay under to count as speech/silence; overridable
+/// via `--vad-threshold-multiplier`, both for VAD auto-stop and for the
+/// leading/trailing trim pass.
+pub const DEFAULT_VAD_THRESHOLD_MULTIPLIER: f32 = 2.0;
+/// How long (in ms) of initial audio is used to calibrate the noise floor.
+const VAD_CALIBRATION_MS: u64 = 300;
+/// Frame length used when trimming silence from a captured buffer.
+const VAD_TRIM_FRAME_MS: u64 = 20;
+
+/// Tracks rolling noise-floor calibration and silence duration for VAD
+/// auto-stop. Lives behind its own mutex since it's mutated from the cpal
+/// callback thread alongside (but independently of) the frame buffer.
+struct VadTracker {
+    noise_floor: f32,
+    calibration_samples_seen: usize,
+    calibration_samples_target: usize,
+    silence_samples: usize,
+    silence_samples_target: usize,
+    threshold_multiplier: f32,
+    /// Set once a frame's energy has exceeded the threshold at least once,
+    /// so the hangover counter can't fire on leading silence before the
+    /// speaker has said anything.
+    has_spoken: bool,
+}
+
+fn rms(samples: &[f32]) -> f32 {
+    if samples.is_empty() {
+        return 0.0;
+    }
+    let sum_sq: f32 = samples.iter().map(|s| s * s).sum();
+    (sum_sq / samples.len() as f32).sqrt()
+}
+
+/// Feeds one callback chunk into the tracker, calibrating the noise floor
+/// over the first `VAD_CALIBRATION_MS` of audio and then accumulating
+/// silence once energy drops back below it. Returns `true` once at least
+/// one speech frame has occurred and silence has since held for the
+/// configured hangover window.
+fn update_vad_and_check_silence(tracker: &mut VadTracker, data: &[f32]) -> bool {
+    let chunk_rms = rms(data);
+
+    if tracker.calibration_samples_seen < tracker.calibration_samples_target {
+        // Running average of energy over the calibration window.
+        let seen = tracker.calibration_samples_seen as f32;
+        let total = seen + data.len() as f32;
+        tracker.noise_floor = (tracker.noise_floor * seen + chunk_rms * data.len() as f32) / total;
+        tracker.calibration_samples_seen += data.len();
+        return false;
+    }
+
+    let threshold = tracker.noise_floor * tracker.threshold_multiplier;
+    if chunk_rms < threshold {
+        tracker.silence_samples += data.len();
+    } else {
+        tracker.has_spoken = true;
+        tracker.silence_samples = 0;
+    }
+
+    tracker.has_spoken && tracker.silence_samples >= tracker.silence_samples_target
+}
+
+/// Estimates a noise floor from the first `VAD_CALIBRATION_MS` of `data`,
+/// for callers (like the post-capture trim pass) that don't have access to
+/// the live `VadTracker` calibrated during `start`.
+pub fn estimate_noise_floor(data: &[f32], sample_rate: u32, channels: u16) -> f32 {
+    let calibration_samples =
+        ((sample_rate as u64 * channels as u64 * VAD_CALIBRATION_MS) / 1000) as usize;
+    rms(&data[..calibration_samples.min(data.len())])
+}
+
+/// Strips leading/trailing windows whose RMS energy stays under
+/// `noise_floor * threshold_multiplier`, so silence around an utterance
+/// isn't uploaded.
+pub fn trim_silence(data: &[f32], sample_rate: u32, channels: u16, noise_floor: f32, threshold_multiplier: f32) -> Vec<f32> {
+    let frame_len =
+        (((sample_rate as u64 * channels as u64 * VAD_TRIM_FRAME_MS) / 1000) as usize).max(1);
+    let threshold = noise_floor * threshold_multiplier;
+
+    let mut start = 0;
+    while start < data.len() {
+        let end = (start + frame_len).min(data.len());
+        if rms(&data[start..end]) > threshold {
+            break;
+        }
+        start = end;
+    }
+
+    let mut end = data.len();
+    while end > start {
+        let begin = end.saturating_sub(frame_len);
+        if rms(&data[begin..end]) > threshold {
+            break;
+        }
+        end = begin;
+    }
+
+    data[start..end].to_vec()
+}
+
+/// Finds a clean place to cut a streaming window: the quietest frame in the
+/// second half of the window (so we don't just chop off every window at a
+/// fixed length mid-word). Returns the whole buffer's length if it isn't
+/// longer than `window_samples`.
+fn find_silence_cut(buffer: &[f32], sample_rate: u32, channels: u16, window_samples: usize) -> usize {
+    if buffer.len() <= window_samples {
+        return buffer.len();
+    }
+
+    let frame_len =
+        (((sample_rate as u64 * channels as u64 * VAD_TRIM_FRAME_MS) / 1000) as usize).max(1);
+    let search_start = window_samples / 2;
+
+    let mut best_idx = window_samples;
+    let mut best_rms = f32::MAX;
+    let mut i = search_start;
+    while i + frame_len <= window_samples {
+        let energy = rms(&buffer[i..i + frame_len]);
+        if energy < best_rms {
+            best_rms = energy;
+            best_idx = i;
+        }
+        i += frame_len;
+    }
+    best_idx
+}
+
+/// Downmixes/resamples a streaming window to the STT target format and
+/// WAV-encodes it in memory (no temp file needed until a caller wants one).
+fn encode_stream_chunk(
+    window: &[f32],
+    sample_rate: u32,
+    channels: u16,
+    sequence: usize,
+) -> Option<StreamChunk> {
+    if window.is_empty() {
+        return None;
+    }
+    let resampled = resample_to(window, sample_rate, channels, TARGET_SAMPLE_RATE);
+
+    let spec = WavSpec {
+        channels: TARGET_CHANNELS,
+        sample_rate: TARGET_SAMPLE_RATE,
+        bits_per_sample: 16,
+        sample_format: HoundSampleFormat::Int,
+    };
+    let mut cursor = std::io::Cursor::new(Vec::new());
+    let mut writer = match WavWriter::new(&mut cursor, spec) {
+        Ok(w) => w,
+        Err(e) => {
+            warn!("Failed to start WAV encoder for stream chunk #{}: {}", sequence, e);
+            return None;
+        }
+    };
+    for &sample in &resampled {
+        let sample_i16 = (sample * 32767.0).clamp(-32768.0, 32767.0) as i16;
+        if let Err(e) = writer.write_sample(sample_i16) {
+            warn!("Failed to encode stream chunk #{}: {}", sequence, e);
+            return None;
+        }
+    }
+    if let Err(e) = writer.finalize() {
+        warn!("Failed to finalize stream chunk #{}: {}", sequence, e);
+        return None;
+    }
+
+    Some(StreamChunk {
+        sequence,
+        wav_bytes: cursor.into_inner(),
+    })
+}
+
 pub struct AudioRecorder {
     // Store frames in an Arc<Mutex> to allow access from audio callback thread
     frames: Arc<Mutex<Vec<f32>>>,
@@ -18,10 +259,27 @@ pub struct AudioRecorder {
     max_duration: Duration,
     max_frames: usize,
     is_recording_flag: Arc<Mutex<bool>>, // Flag to signal recording state
+    device_name: Option<String>,
+    vad_enabled: bool,
+    vad_silence_ms: u64,
+    vad_threshold_multiplier: f32,
+    /// Notified once, from whichever thread owns the cpal audio callback,
+    /// when capture decides to stop itself (VAD silence or hitting
+    /// `max_time`) rather than being asked to via `stop()`. A caller that
+    /// only reacts to its own commands (like `controller::AudioController`)
+    /// needs this to learn about an auto-stop at all.
+    auto_stop_tx: Option<crossbeam_channel::Sender<()>>,
 }
 
 impl AudioRecorder {
     pub fn new(max_time_seconds: u32) -> Result<Self> {
+        Self::with_device(max_time_seconds, None)
+    }
+
+    /// Like `new`, but records from the named device instead of the host
+    /// default. `device_name` is resolved lazily in `start`, so an
+    /// unplugged device can still fall back to default at record time.
+    pub fn with_device(max_time_seconds: u32, device_name: Option<String>) -> Result<Self> {
         let max_duration = Duration::from_secs(max_time_seconds as u64);
         let max_frames = (MAX_SAMPLE_RATE * MAX_CHANNELS as u32 * max_time_seconds) as usize;
         info!(
@@ -36,9 +294,35 @@ impl AudioRecorder {
             max_duration,
             max_frames,
             is_recording_flag: Arc::new(Mutex::new(false)),
+            device_name,
+            vad_enabled: false,
+            vad_silence_ms: 800,
+            vad_threshold_multiplier: DEFAULT_VAD_THRESHOLD_MULTIPLIER,
+            auto_stop_tx: None,
         })
     }
 
+    /// Enables voice-activity auto-stop: once the running energy stays
+    /// below `noise_floor * threshold_multiplier` for `silence_ms` (and at
+    /// least one frame of speech has been seen), `start`'s capture callback
+    /// flips off `is_recording_flag` on its own, the same way it already
+    /// does when `max_time` is hit.
+    pub fn set_vad(&mut self, enabled: bool, silence_ms: u64, threshold_multiplier: f32) {
+        self.vad_enabled = enabled;
+        self.vad_silence_ms = silence_ms;
+        self.vad_threshold_multiplier = threshold_multiplier;
+    }
+
+    /// Registers a channel that gets a single message pushed to it whenever
+    /// the capture callback stops recording on its own (VAD silence or
+    /// `max_time`) instead of through an explicit `stop()` call. Without
+    /// this, a caller that doesn't poll `is_recording()` — like the
+    /// controller thread, which just blocks on its command channel — has no
+    /// way to learn an auto-stop happened at all.
+    pub fn set_auto_stop_notifier(&mut self, tx: crossbeam_channel::Sender<()>) {
+        self.auto_stop_tx = Some(tx);
+    }
+
     pub fn start(&mut self) -> Result<()> {
         if self.is_recording() {
             warn!("Recording already in progress.");
@@ -49,9 +333,7 @@ impl AudioRecorder {
         self.frames.lock().expect("Mutex poisoned").clear();
 
         let host = cpal::default_host();
-        let device = host
-            .default_input_device()
-            .context("No default input device available")?;
+        let device = resolve_input_device(&host, self.device_name.as_deref())?;
         info!("Using audio input device: {}", device.name()?);
 
         // Check if format is supported
@@ -93,6 +375,22 @@ impl AudioRecorder {
         let max_frames = self.max_frames;
         let is_recording_flag_callback = self.is_recording_flag.clone();
         let stop_recording_flag_callback = self.is_recording_flag.clone(); // Need to access it to stop
+        let auto_stop_tx_callback = self.auto_stop_tx.clone();
+
+        let vad_tracker = if self.vad_enabled {
+            let samples_per_ms = (config.sample_rate.0 as u64 * config.channels as u64) / 1000;
+            Some(Arc::new(Mutex::new(VadTracker {
+                noise_floor: 0.0,
+                calibration_samples_seen: 0,
+                calibration_samples_target: (samples_per_ms * VAD_CALIBRATION_MS) as usize,
+                silence_samples: 0,
+                silence_samples_target: (samples_per_ms * self.vad_silence_ms) as usize,
+                threshold_multiplier: self.vad_threshold_multiplier,
+                has_spoken: false,
+            })))
+        } else {
+            None
+        };
 
         let err_fn = |err| panic!("An error occurred on the audio stream: {}", err);
 
@@ -109,6 +407,18 @@ impl AudioRecorder {
                     return;
                 }
 
+                if let Some(tracker) = &vad_tracker {
+                    let mut tracker = tracker.lock().expect("Mutex poisoned");
+                    if update_vad_and_check_silence(&mut tracker, data) {
+                        info!("Silence detected. Stopping capture (VAD).");
+                        *stop_recording_flag_callback.lock().expect("Mutex poisoned") = false;
+                        if let Some(tx) = &auto_stop_tx_callback {
+                            let _ = tx.send(());
+                        }
+                        return;
+                    }
+                }
+
                 let mut frame_buffer = shared_frames.lock().expect("Mutex poisoned");
 
                 // Check if max duration reached
@@ -117,6 +427,9 @@ impl AudioRecorder {
                         warn!("Max recording time reached. Stopping capture.");
                         // Signal stopping (best effort, stream might run a bit longer)
                         *stop_recording_flag_callback.lock().expect("Mutex poisoned") = false;
+                        if let Some(tx) = &auto_stop_tx_callback {
+                            let _ = tx.send(());
+                        }
                     }
                     return; // Don't add more frames
                 }
@@ -140,6 +453,129 @@ impl AudioRecorder {
         Ok(())
     }
 
+    /// Starts a `--stream` capture: frames flow into a fixed-capacity ring
+    /// buffer instead of one growing `Vec<f32>`, and a consumer thread
+    /// slices them into overlapping windows (cut at the quietest point near
+    /// the boundary so words aren't split), WAV-encodes each, and sends it
+    /// over `chunk_tx` as soon as it's ready. `stop` still halts the
+    /// underlying cpal stream; its returned buffer will be empty since
+    /// captured audio is drained by the consumer thread instead.
+    pub fn start_streaming(&mut self, chunk_tx: crossbeam_channel::Sender<StreamChunk>) -> Result<()> {
+        if self.is_recording() {
+            warn!("Recording already in progress.");
+            return Ok(());
+        }
+
+        let host = cpal::default_host();
+        let device = resolve_input_device(&host, self.device_name.as_deref())?;
+        info!("Using audio input device: {}", device.name()?);
+
+        let supported_configs: Vec<SupportedStreamConfigRange> =
+            device.supported_input_configs()?.collect();
+        let supported = supported_configs
+            .iter()
+            .filter(|c| c.channels() <= MAX_CHANNELS && c.min_sample_rate().0 <= MAX_SAMPLE_RATE)
+            .max_by_key(|c| (c.channels(), c.max_sample_rate().0))
+            .ok_or_else(|| anyhow!("No supported audio configurations found"))?;
+
+        let config = StreamConfig {
+            channels: supported.channels(),
+            sample_rate: SampleRate(std::cmp::min(
+                supported.max_sample_rate().0,
+                MAX_SAMPLE_RATE,
+            )),
+            buffer_size: cpal::BufferSize::Default,
+        };
+        info!(
+            "Streaming audio format: {}Hz, {}ch",
+            config.sample_rate.0, config.channels
+        );
+
+        let ring_capacity = config.sample_rate.0 as usize * config.channels as usize * STREAM_RING_SECONDS;
+        let ring = ringbuf::HeapRb::<f32>::new(ring_capacity);
+        let (mut producer, mut consumer) = ring.split();
+
+        let is_recording_flag_callback = self.is_recording_flag.clone();
+        let err_fn = |err| panic!("An error occurred on the audio stream: {}", err);
+
+        let stream = device.build_input_stream(
+            &config,
+            move |data: &[f32], _: &cpal::InputCallbackInfo| {
+                if !*is_recording_flag_callback.lock().expect("Mutex poisoned") {
+                    return;
+                }
+                for &sample in data {
+                    // Ring buffer is sized generously (STREAM_RING_SECONDS);
+                    // if the consumer thread ever falls this far behind we
+                    // drop samples rather than block the audio thread.
+                    let _ = producer.try_push(sample);
+                }
+            },
+            err_fn,
+            None,
+        )?;
+
+        let sample_rate = config.sample_rate.0;
+        let channels = config.channels;
+        let window_samples = (STREAM_WINDOW_SECONDS * sample_rate as f32 * channels as f32) as usize;
+        let overlap_samples = (STREAM_OVERLAP_SECONDS * sample_rate as f32 * channels as f32) as usize;
+        let is_recording_flag_consumer = self.is_recording_flag.clone();
+
+        // Mark capture as running before the consumer thread's first check
+        // of the flag, so it doesn't see the initial `false` and exit.
+        *self.is_recording_flag.lock().expect("Mutex poisoned") = true;
+
+        std::thread::spawn(move || {
+            let mut buffer: Vec<f32> = Vec::new();
+            let mut sequence = 0usize;
+            loop {
+                while let Some(sample) = consumer.try_pop() {
+                    buffer.push(sample);
+                }
+
+                let still_recording = *is_recording_flag_consumer.lock().expect("Mutex poisoned");
+
+                if buffer.len() >= window_samples {
+                    let cut = find_silence_cut(&buffer, sample_rate, channels, window_samples);
+                    if let Some(chunk) = encode_stream_chunk(&buffer[..cut], sample_rate, channels, sequence) {
+                        sequence += 1;
+                        if chunk_tx.send(chunk).is_err() {
+                            break; // Receiver gone; stop producing chunks.
+                        }
+                    }
+                    // Keep the overlap tail so the next window picks up
+                    // just before this one was cut.
+                    let keep_from = cut.saturating_sub(overlap_samples);
+                    buffer.drain(..keep_from);
+                }
+
+                if !still_recording && buffer.len() < window_samples {
+                    if !buffer.is_empty() {
+                        if let Some(chunk) =
+                            encode_stream_chunk(&buffer, sample_rate, channels, sequence)
+                        {
+                            let _ = chunk_tx.send(chunk);
+                        }
+                    }
+                    break;
+                }
+
+                std::thread::sleep(Duration::from_millis(50));
+            }
+            debug!("Streaming chunk thread finished.");
+        });
+
+        self.stream_config = Some(config);
+        if let Err(e) = stream.play() {
+            *self.is_recording_flag.lock().expect("Mutex poisoned") = false;
+            return Err(e.into());
+        }
+        self.stream = Some(stream);
+        info!("Streaming audio recording started.");
+
+        Ok(())
+    }
+
     pub fn stop(&mut self) -> Result<Option<(StreamConfig, Vec<f32>)>> {
         // Signal the callback to stop adding data
         *self.is_recording_flag.lock().expect("Mutex poisoned") = false;
@@ -171,34 +607,225 @@ impl AudioRecorder {
     // Stream dropping should handle cleanup.
 }
 
+/// Sample rate and channel count Whisper-class STT models expect.
+pub const TARGET_SAMPLE_RATE: u32 = 16000;
+pub const TARGET_CHANNELS: u16 = 1;
+
+/// Downmixes `data` to mono and resamples it to `dst_rate` with a
+/// band-limited windowed-sinc (Lanczos) kernel, so captured audio at
+/// whatever rate/channel count the device happened to support ends up in
+/// the shape `TARGET_SAMPLE_RATE`/`TARGET_CHANNELS` STT backends expect.
+pub fn resample_to(data: &[f32], src_rate: u32, src_channels: u16, dst_rate: u32) -> Vec<f32> {
+    let mono = downmix_to_mono(data, src_channels);
+    if src_rate == dst_rate {
+        return mono;
+    }
+    lanczos_resample(&mono, src_rate, dst_rate)
+}
+
+fn downmix_to_mono(data: &[f32], channels: u16) -> Vec<f32> {
+    if channels <= 1 {
+        return data.to_vec();
+    }
+    let channels = channels as usize;
+    data.chunks(channels)
+        .map(|frame| frame.iter().sum::<f32>() / frame.len() as f32)
+        .collect()
+}
+
+/// Window half-width, in taps, of the Lanczos kernel.
+const LANCZOS_A: f64 = 3.0;
+
+fn sinc(x: f64) -> f64 {
+    if x.abs() < 1e-9 {
+        1.0
+    } else {
+        let px = std::f64::consts::PI * x;
+        px.sin() / px
+    }
+}
+
+fn lanczos_resample(mono: &[f32], src_rate: u32, dst_rate: u32) -> Vec<f32> {
+    if mono.is_empty() || src_rate == 0 || dst_rate == 0 {
+        return Vec::new();
+    }
+
+    let ratio = src_rate as f64 / dst_rate as f64;
+    let out_len = (mono.len() as f64 / ratio).round() as usize;
+    let mut out = Vec::with_capacity(out_len);
+
+    for n in 0..out_len {
+        // Source-domain position this output sample falls at.
+        let t = n as f64 * ratio;
+        let t_floor = t.floor();
+        let lo = (t_floor - LANCZOS_A + 1.0) as i64;
+        let hi = (t_floor + LANCZOS_A) as i64;
+
+        let mut weighted_sum = 0.0;
+        let mut weight_total = 0.0;
+        for i in lo..=hi {
+            if i < 0 || i as usize >= mono.len() {
+                continue;
+            }
+            let x = t - i as f64;
+            let weight = sinc(x) * sinc(x / LANCZOS_A);
+            weighted_sum += weight * mono[i as usize] as f64;
+            weight_total += weight;
+        }
+
+        // Normalize by the summed weights (rather than assuming they sum to
+        // 1) to preserve gain even near the clipped edges of the signal.
+        let sample = if weight_total.abs() > 1e-9 {
+            weighted_sum / weight_total
+        } else {
+            0.0
+        };
+        out.push(sample as f32);
+    }
+
+    out
+}
+
 // Helper function to save f32 samples to a WAV file
 // Note: Standard WAV often uses Int16. APIs might accept float, but saving as Int16 is safer.
-pub fn save_f32_to_wav(path: &Path, data: &[f32], sample_rate: u32, channels: u16) -> Result<()> {
+pub fn save_f32_to_wav(path: &Path, data: &[f32], sample_rate: u32, channels: u16, format: AudioFormat) -> Result<()> {
     if data.is_empty() {
         return Err(anyhow!("No audio data to save."));
     }
 
+    match format {
+        AudioFormat::Int16 => write_wav_int(path, data, sample_rate, channels, 16)?,
+        AudioFormat::Int24 => write_wav_int(path, data, sample_rate, channels, 24)?,
+        AudioFormat::Float32 => write_wav_float(path, data, sample_rate, channels)?,
+        AudioFormat::Flac => write_flac(path, data, sample_rate, channels)?,
+    }
+
+    debug!(
+        "Successfully saved {} samples to {} ({:?})",
+        data.len(),
+        path.display(),
+        format
+    );
+    Ok(())
+}
+
+/// Writes 16- or 24-bit integer PCM, scaling by the format's full-scale
+/// value and clamping before quantizing.
+fn write_wav_int(path: &Path, data: &[f32], sample_rate: u32, channels: u16, bits_per_sample: u16) -> Result<()> {
     let spec = WavSpec {
         channels,
         sample_rate,
-        bits_per_sample: 16, // Save as 16-bit integer PCM
+        bits_per_sample,
         sample_format: HoundSampleFormat::Int,
     };
-
     let mut writer = WavWriter::create(path, spec)
         .with_context(|| format!("Failed to create WAV writer for '{}'", path.display()))?;
 
-    // Convert f32 samples (-1.0 to 1.0) to i16 (-32768 to 32767)
-    for &sample_f32 in data {
-        let sample_i16 = (sample_f32 * 32767.0).clamp(-32768.0, 32767.0) as i16;
-        writer.write_sample(sample_i16)?;
+    if bits_per_sample == 24 {
+        let full_scale = (1i32 << 23) - 1;
+        for &sample_f32 in data {
+            let sample_i24 = (sample_f32 * full_scale as f32).clamp(-(full_scale as f32) - 1.0, full_scale as f32) as i32;
+            writer.write_sample(sample_i24)?;
+        }
+    } else {
+        for &sample_f32 in data {
+            let sample_i16 = (sample_f32 * 32767.0).clamp(-32768.0, 32767.0) as i16;
+            writer.write_sample(sample_i16)?;
+        }
     }
 
     writer.finalize()?;
-    debug!(
-        "Successfully saved {} samples to {}",
-        data.len(),
-        path.display()
-    );
     Ok(())
 }
+
+/// Writes the f32 samples verbatim, with no quantization at all, for
+/// backends that accept float WAV and would otherwise lose precision cpal
+/// already captured.
+fn write_wav_float(path: &Path, data: &[f32], sample_rate: u32, channels: u16) -> Result<()> {
+    let spec = WavSpec {
+        channels,
+        sample_rate,
+        bits_per_sample: 32,
+        sample_format: HoundSampleFormat::Float,
+    };
+    let mut writer = WavWriter::create(path, spec)
+        .with_context(|| format!("Failed to create WAV writer for '{}'", path.display()))?;
+    for &sample_f32 in data {
+        writer.write_sample(sample_f32)?;
+    }
+    writer.finalize()?;
+    Ok(())
+}
+
+/// Lossless-but-compact alternative to raw PCM, for slower uplinks. Encodes
+/// at 16-bit depth (matching the previous default precision) since FLAC's
+/// compression gain over PCM matters more here than bit depth.
+fn write_flac(path: &Path, data: &[f32], sample_rate: u32, channels: u16) -> Result<()> {
+    let samples_i32: Vec<i32> = data
+        .iter()
+        .map(|&s| (s * 32767.0).clamp(-32768.0, 32767.0) as i32)
+        .collect();
+
+    let config = flacenc::config::Encoder::default();
+    let source = flacenc::source::MemSource::from_samples(&samples_i32, channels as usize, 16, sample_rate as usize);
+    let stream = flacenc::encode_with_fixed_block_size(&config, source, config.block_size)
+        .map_err(|e| anyhow!("FLAC encoding failed: {:?}", e))?;
+
+    let mut sink = flacenc::bitsink::ByteSink::new();
+    stream
+        .write(&mut sink)
+        .map_err(|e| anyhow!("FLAC bitstream write failed: {:?}", e))?;
+    std::fs::write(path, sink.as_slice()).with_context(|| format!("Failed to write FLAC file '{}'", path.display()))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resample_to_is_a_noop_when_rates_match() {
+        let data = vec![0.1, 0.2, -0.3, 0.4];
+        let result = resample_to(&data, 16000, 1, 16000);
+        assert_eq!(result, data);
+    }
+
+    #[test]
+    fn resample_to_downmixes_before_resampling() {
+        // Two channels of a constant value should downmix to that same
+        // constant mono value at half the sample count; rates match here so
+        // resampling itself is a no-op.
+        let data = vec![0.5, 0.5, 0.5, 0.5, 0.5, 0.5];
+        let result = resample_to(&data, 16000, 2, 16000);
+        assert_eq!(result.len(), 3);
+        assert!(result.iter().all(|&s| (s - 0.5).abs() < 1e-6));
+    }
+
+    #[test]
+    fn lanczos_resample_preserves_a_constant_signal() {
+        let mono = vec![0.25f32; 1000];
+        let upsampled = lanczos_resample(&mono, 8000, 16000);
+        let downsampled = lanczos_resample(&mono, 16000, 8000);
+
+        // Away from the edges (where the kernel has fewer in-range taps to
+        // normalize against), a DC signal should come back out unchanged.
+        for &s in &upsampled[20..upsampled.len() - 20] {
+            assert!((s - 0.25).abs() < 1e-3, "sample {} too far from 0.25", s);
+        }
+        for &s in &downsampled[20..downsampled.len() - 20] {
+            assert!((s - 0.25).abs() < 1e-3, "sample {} too far from 0.25", s);
+        }
+    }
+
+    #[test]
+    fn lanczos_resample_output_length_matches_the_rate_ratio() {
+        let mono = vec![0.0f32; 1600];
+        assert_eq!(lanczos_resample(&mono, 8000, 16000).len(), 3200);
+        assert_eq!(lanczos_resample(&mono, 16000, 8000).len(), 800);
+    }
+
+    #[test]
+    fn lanczos_resample_handles_empty_input() {
+        assert!(lanczos_resample(&[], 16000, 8000).is_empty());
+    }
+}
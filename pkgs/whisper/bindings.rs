@@ -0,0 +1,248 @@
+use crate::config::{OutputType, Service};
+use crate::hotkey::HotkeyEvent;
+use anyhow::{anyhow, Context, Result};
+use serde::Deserialize;
+use std::path::Path;
+
+/// A single logical modifier key, independent of which physical left/right
+/// scancode ends up producing it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Modifier {
+    Ctrl,
+    Alt,
+    Shift,
+    Meta,
+}
+
+impl Modifier {
+    fn parse(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "ctrl" | "control" => Ok(Modifier::Ctrl),
+            "alt" => Ok(Modifier::Alt),
+            "shift" => Ok(Modifier::Shift),
+            "meta" | "super" | "win" | "cmd" | "command" => Ok(Modifier::Meta),
+            _ => Err(anyhow!("Unsupported modifier: {}", s)),
+        }
+    }
+
+    /// Parses a `+`-delimited combo string (e.g. `"Ctrl+Alt"`) as used by the
+    /// legacy `--modifier` flag, which now accepts more than one modifier.
+    fn parse_combo(s: &str) -> Result<Vec<Self>> {
+        s.split('+')
+            .map(str::trim)
+            .filter(|part| !part.is_empty())
+            .map(Modifier::parse)
+            .collect()
+    }
+}
+
+/// One parsed chord -> action binding, modeled on the swhkd/sohkd
+/// `Hotkey { keysym, modifiers, command, consume, mode }` design.
+#[derive(Debug, Clone)]
+pub struct Hotkey {
+    pub modifiers: Vec<Modifier>,
+    pub key: String,
+    pub event: HotkeyEvent,
+    /// Grab the matched keys so the chord doesn't leak to the focused app.
+    pub consume: bool,
+    /// Binding set this chord is active in; `None` means "all modes".
+    pub mode: Option<String>,
+}
+
+/// TOML-deserializable form of a binding before it's resolved into a `Hotkey`.
+#[derive(Debug, Deserialize)]
+struct RawHotkey {
+    modifiers: Vec<String>,
+    key: String,
+    action: String,
+    #[serde(default)]
+    arg: Option<String>,
+    #[serde(default)]
+    consume: bool,
+    #[serde(default)]
+    mode: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct RawBindingFile {
+    #[serde(rename = "hotkey", default)]
+    hotkeys: Vec<RawHotkey>,
+}
+
+impl RawHotkey {
+    fn resolve(self) -> Result<Hotkey> {
+        let modifiers = self
+            .modifiers
+            .iter()
+            .map(|m| Modifier::parse(m))
+            .collect::<Result<Vec<_>>>()?;
+
+        let event = match self.action.to_lowercase().as_str() {
+            "toggle_recording" => HotkeyEvent::ToggleRecording,
+            "push_to_talk_start" => HotkeyEvent::PushToTalkStart,
+            "push_to_talk_stop" => HotkeyEvent::PushToTalkStop,
+            "cancel_recording" => HotkeyEvent::CancelRecording,
+            "switch_service" => {
+                let arg = self
+                    .arg
+                    .as_deref()
+                    .ok_or_else(|| anyhow!("switch_service binding requires an 'arg'"))?;
+                let service = match arg.to_lowercase().as_str() {
+                    "openai" => Service::OpenAI,
+                    "replicate" => Service::Replicate,
+                    "elevenlabs" => Service::ElevenLabs,
+                    "deepgram" => Service::Deepgram,
+                    other => return Err(anyhow!("Unknown service in binding: {}", other)),
+                };
+                HotkeyEvent::SwitchService(service)
+            }
+            "change_output" => {
+                let arg = self
+                    .arg
+                    .as_deref()
+                    .ok_or_else(|| anyhow!("change_output binding requires an 'arg'"))?;
+                let output = match arg.to_lowercase().as_str() {
+                    "clipboard" => OutputType::Clipboard,
+                    "paste" => OutputType::Paste,
+                    "file" => OutputType::File,
+                    "stdout" => OutputType::Stdout,
+                    other => return Err(anyhow!("Unknown output mode in binding: {}", other)),
+                };
+                HotkeyEvent::ChangeOutput(output)
+            }
+            "switch_mode" => {
+                let arg = self
+                    .arg
+                    .ok_or_else(|| anyhow!("switch_mode binding requires an 'arg'"))?;
+                HotkeyEvent::SwitchMode(arg)
+            }
+            other => return Err(anyhow!("Unknown hotkey action: {}", other)),
+        };
+
+        Ok(Hotkey {
+            modifiers,
+            key: self.key,
+            event,
+            consume: self.consume,
+            mode: self.mode,
+        })
+    }
+}
+
+/// Parse a binding config file (TOML) into the binding table consulted by
+/// both `listen_rdev` and `listen_wayland`.
+pub fn load_bindings(path: &Path) -> Result<Vec<Hotkey>> {
+    let raw = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read hotkey config file: {}", path.display()))?;
+    let parsed: RawBindingFile = toml::from_str(&raw)
+        .with_context(|| format!("Failed to parse hotkey config file: {}", path.display()))?;
+    parsed
+        .hotkeys
+        .into_iter()
+        .map(RawHotkey::resolve)
+        .collect()
+}
+
+/// Fallback binding table built from the legacy `--modifier`/`--key` flags,
+/// used when no `--hotkey-config` file is supplied. `modifier` may be a
+/// single modifier (`"Ctrl"`) or a `+`-delimited combo (`"Ctrl+Alt"`).
+/// `consume` mirrors the top-level `--grab` flag; `ptt` mirrors `--ptt` and
+/// switches the single chord from toggle to push-to-talk.
+pub fn default_bindings(modifier: &str, key: &str, consume: bool, ptt: bool) -> Result<Vec<Hotkey>> {
+    let event = if ptt {
+        HotkeyEvent::PushToTalkStart
+    } else {
+        HotkeyEvent::ToggleRecording
+    };
+    Ok(vec![Hotkey {
+        modifiers: Modifier::parse_combo(modifier)?,
+        key: key.to_string(),
+        event,
+        consume,
+        mode: None,
+    }])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn modifier_parse_accepts_known_aliases_case_insensitively() {
+        assert_eq!(Modifier::parse("ctrl").unwrap(), Modifier::Ctrl);
+        assert_eq!(Modifier::parse("Control").unwrap(), Modifier::Ctrl);
+        assert_eq!(Modifier::parse("ALT").unwrap(), Modifier::Alt);
+        assert_eq!(Modifier::parse("shift").unwrap(), Modifier::Shift);
+        assert_eq!(Modifier::parse("Meta").unwrap(), Modifier::Meta);
+        assert_eq!(Modifier::parse("super").unwrap(), Modifier::Meta);
+        assert_eq!(Modifier::parse("win").unwrap(), Modifier::Meta);
+        assert_eq!(Modifier::parse("cmd").unwrap(), Modifier::Meta);
+        assert_eq!(Modifier::parse("command").unwrap(), Modifier::Meta);
+        assert!(Modifier::parse("hyper").is_err());
+    }
+
+    #[test]
+    fn modifier_parse_combo_splits_on_plus_and_trims_whitespace() {
+        assert_eq!(Modifier::parse_combo("Ctrl").unwrap(), vec![Modifier::Ctrl]);
+        assert_eq!(
+            Modifier::parse_combo("Ctrl+Alt").unwrap(),
+            vec![Modifier::Ctrl, Modifier::Alt]
+        );
+        assert_eq!(
+            Modifier::parse_combo(" Ctrl + Alt + Shift ").unwrap(),
+            vec![Modifier::Ctrl, Modifier::Alt, Modifier::Shift]
+        );
+        assert!(Modifier::parse_combo("Ctrl+Nonsense").is_err());
+    }
+
+    fn raw_hotkey(modifiers: &[&str], action: &str, arg: Option<&str>) -> RawHotkey {
+        RawHotkey {
+            modifiers: modifiers.iter().map(|m| m.to_string()).collect(),
+            key: "A".to_string(),
+            action: action.to_string(),
+            arg: arg.map(|a| a.to_string()),
+            consume: false,
+            mode: None,
+        }
+    }
+
+    #[test]
+    fn raw_hotkey_resolve_maps_simple_actions() {
+        let hotkey = raw_hotkey(&["Ctrl"], "toggle_recording", None).resolve().unwrap();
+        assert_eq!(hotkey.modifiers, vec![Modifier::Ctrl]);
+        assert!(matches!(hotkey.event, HotkeyEvent::ToggleRecording));
+
+        assert!(matches!(
+            raw_hotkey(&[], "push_to_talk_start", None).resolve().unwrap().event,
+            HotkeyEvent::PushToTalkStart
+        ));
+        assert!(matches!(
+            raw_hotkey(&[], "cancel_recording", None).resolve().unwrap().event,
+            HotkeyEvent::CancelRecording
+        ));
+    }
+
+    #[test]
+    fn raw_hotkey_resolve_requires_arg_for_switch_service_and_change_output() {
+        assert!(raw_hotkey(&[], "switch_service", None).resolve().is_err());
+        assert!(raw_hotkey(&[], "change_output", None).resolve().is_err());
+        assert!(raw_hotkey(&[], "switch_mode", None).resolve().is_err());
+
+        assert!(matches!(
+            raw_hotkey(&[], "switch_service", Some("openai")).resolve().unwrap().event,
+            HotkeyEvent::SwitchService(Service::OpenAI)
+        ));
+        assert!(matches!(
+            raw_hotkey(&[], "change_output", Some("paste")).resolve().unwrap().event,
+            HotkeyEvent::ChangeOutput(OutputType::Paste)
+        ));
+    }
+
+    #[test]
+    fn raw_hotkey_resolve_rejects_unknown_action_or_arg() {
+        assert!(raw_hotkey(&[], "do_a_backflip", None).resolve().is_err());
+        assert!(raw_hotkey(&[], "switch_service", Some("bing")).resolve().is_err());
+        assert!(raw_hotkey(&[], "change_output", Some("fax")).resolve().is_err());
+        assert!(raw_hotkey(&["not-a-modifier"], "toggle_recording", None).resolve().is_err());
+    }
+}
@@ -26,7 +26,8 @@ pub struct Config {
     #[arg(short, long, env = "DICTATION_FILE")]
     pub file: Option<PathBuf>,
 
-    /// Modifier key for hotkey (e.g., Control, Alt, Shift, Meta/Super/Win/Cmd)
+    /// Modifier key(s) for hotkey (e.g., Control, Alt, Shift, Meta/Super/Win/Cmd).
+    /// Combine several with `+`, e.g. "Control+Alt", to require all of them held.
     #[arg(short = 'm', long, default_value = "Control", env = "DICTATION_MOD")]
     pub modifier: String, // Keep as String for flexibility, parse in hotkey module
 
@@ -34,6 +35,31 @@ pub struct Config {
     #[arg(short = 'g', long, default_value = "F11", env = "DICTATION_KEY")]
     pub key: String, // Keep as String, parse in hotkey module
 
+    /// Path to a TOML hotkey binding file (see the `bindings` module for the
+    /// `[[hotkey]]` schema). When set, this replaces the single
+    /// `--modifier`/`--key` chord with an arbitrary table of chords, modes,
+    /// and actions.
+    #[arg(long, env = "DICTATION_HOTKEY_CONFIG")]
+    pub hotkey_config: Option<PathBuf>,
+
+    /// Consume the legacy `--modifier`/`--key` chord so the keystroke
+    /// doesn't also reach the focused application (Wayland/evdev only): the
+    /// keyboard devices are grabbed for as long as the listener runs, and
+    /// every event other than the matched chord's key is re-emitted through
+    /// a uinput virtual device. Only affects the fallback binding built when
+    /// `--hotkey-config` is unset; bindings in that file set their own
+    /// `consume` flag instead.
+    #[arg(long, env = "DICTATION_GRAB")]
+    pub grab: bool,
+
+    /// Push-to-talk mode: hold the legacy `--modifier`/`--key` chord to
+    /// record, release to stop, instead of toggling on alternating presses.
+    /// Only affects the fallback binding built when `--hotkey-config` is
+    /// unset; a binding file can already mix `push_to_talk_start`/`_stop`
+    /// actions freely.
+    #[arg(long, env = "DICTATION_PTT")]
+    pub ptt: bool,
+
     /// Number of API retries on failure
     #[arg(short, long, default_value_t = 3, env = "DICTATION_RETRIES")]
     pub retries: u32,
@@ -42,6 +68,116 @@ pub struct Config {
     #[arg(long, default_value_t = 300, env = "DICTATION_MAX_TIME")]
     pub max_time: u32,
 
+    /// Name of the audio input device to record from (as reported by
+    /// `--list-devices`). Falls back to the system default if unset or if
+    /// no device with this name is currently connected.
+    #[arg(long, env = "DICTATION_DEVICE")]
+    pub device: Option<String>,
+
+    /// List available audio input devices and exit
+    #[arg(long)]
+    pub list_devices: bool,
+
+    /// Auto-stop recording after the user stops speaking, instead of
+    /// requiring a second hotkey press or hitting --max-time.
+    #[arg(long, env = "DICTATION_VAD")]
+    pub vad: bool,
+
+    /// How long (ms) energy must stay below the speech threshold (the
+    /// calibrated noise floor times --vad-threshold-multiplier) before
+    /// --vad stops the recording.
+    #[arg(long, default_value_t = 800, env = "DICTATION_VAD_SILENCE_MS")]
+    pub vad_silence_ms: u64,
+
+    /// How many times above the calibrated noise floor a frame's RMS energy
+    /// must rise to count as speech for --vad (roughly a 6 dB margin at the
+    /// default 2.0). Raise it in noisy rooms to avoid false speech triggers;
+    /// lower it if quiet speech isn't keeping the recording open.
+    #[arg(long, default_value_t = 2.0, env = "DICTATION_VAD_THRESHOLD_MULTIPLIER")]
+    pub vad_threshold_multiplier: f32,
+
+    /// Stream audio in overlapping windows and transcribe/output each as it
+    /// completes, instead of waiting for the whole recording to stop.
+    #[arg(long, env = "DICTATION_STREAM")]
+    pub stream: bool,
+
+    /// Encoding used for the scratch/upload audio file. `int16` matches the
+    /// previous hardcoded behavior; `int24`/`float32` keep more of the
+    /// precision cpal actually captured, for backends that accept them;
+    /// `flac` is lossless but smaller than raw PCM, for slower uplinks.
+    #[arg(long, value_enum, default_value_t = AudioFormat::default(), env = "DICTATION_AUDIO_FORMAT")]
+    pub audio_format: AudioFormat,
+
+    /// Path to a local whisper.cpp model file. Required when `--service
+    /// local` is selected.
+    #[arg(long, env = "DICTATION_MODEL")]
+    pub model: Option<PathBuf>,
+
+    /// Spoken language hint for local transcription (e.g. "en"). Omit to
+    /// let whisper auto-detect. Ignored by the remote services.
+    #[arg(long, env = "DICTATION_LANGUAGE")]
+    pub language: Option<String>,
+
+    /// Custom base URL for the OpenAI-compatible `/v1/audio/transcriptions`
+    /// endpoint, for targeting Groq, Azure OpenAI, a self-hosted
+    /// faster-whisper server, or anything else that speaks the same API.
+    /// Only consulted by `--service openai`; defaults to OpenAI's API.
+    #[arg(long, env = "DICTATION_BASE_URL")]
+    pub base_url: Option<String>,
+
+    /// Model name sent to the OpenAI-compatible endpoint (e.g.
+    /// `whisper-large-v3` on Groq). Only consulted by `--service openai`;
+    /// defaults to OpenAI's transcription model.
+    #[arg(long, env = "DICTATION_MODEL_NAME")]
+    pub model_name: Option<String>,
+
+    /// Local address to expose a Prometheus-format `/metrics` scrape
+    /// endpoint on (requires building with `--features metrics`).
+    #[cfg(feature = "metrics")]
+    #[arg(long, env = "DICTATION_METRICS_ADDR")]
+    pub metrics_addr: Option<std::net::SocketAddr>,
+
+    /// Prometheus Pushgateway URL to push accumulated metrics to once on
+    /// process exit, for a short-lived CLI run that a `--metrics-addr`
+    /// scrape would otherwise miss entirely.
+    #[cfg(feature = "metrics")]
+    #[arg(long, env = "DICTATION_METRICS_PUSHGATEWAY")]
+    pub metrics_pushgateway: Option<String>,
+
+    /// Deflate-compress cached recordings on write (stored as `.wav.zz`/
+    /// `.flac.zz`) and retain them in the cache directory instead of
+    /// deleting them right after upload, transparently decompressing a
+    /// scratch copy whenever one needs to be re-uploaded. Retention is
+    /// bounded by `--max-cache-bytes` and `cleanup_old_files`'s age limit.
+    #[arg(long, env = "DICTATION_COMPRESS_CACHE")]
+    pub compress_cache: bool,
+
+    /// Total size budget, in bytes, for retained cached recordings. Once
+    /// age-based cleanup still leaves the cache directory over this, the
+    /// oldest files are evicted until it's back under. Unset means no cap.
+    #[arg(long, env = "DICTATION_MAX_CACHE_BYTES")]
+    pub max_cache_bytes: Option<u64>,
+
+    /// Play short tones on recording start/stop and transcription
+    /// success/failure, for hands-free confirmation when the hotkey
+    /// workflow happens while looking at another window.
+    #[arg(long, env = "DICTATION_SOUND")]
+    pub sound: bool,
+
+    /// Show a native desktop notification on transcription success (with a
+    /// preview of the text) and failure (with the reason), independent of
+    /// `--output`. Useful since the tool usually runs with no visible
+    /// terminal to read a failure from.
+    #[arg(long, env = "DICTATION_NOTIFY")]
+    pub notify: bool,
+
+    /// Send one `toggle` command to an already-running dictation process's
+    /// control socket and exit, instead of starting the engine. Lets a
+    /// window-manager keybinding drive an always-running daemon without
+    /// this invocation owning a terminal.
+    #[arg(long)]
+    pub toggle: bool,
+
     // --- Resolved values (populated after parsing) ---
     #[clap(skip)]
     pub api_key: String,
@@ -54,14 +190,23 @@ pub enum Service {
     #[serde(rename = "replicate")]
     Replicate,
     ElevenLabs,
+    /// Fast, cheap pre-recorded transcription via Deepgram's `/v1/listen`.
+    Deepgram,
+    /// Offline transcription via a bundled whisper.cpp model (see
+    /// `--model`/`--language`); needs no API key or network access.
+    Local,
 }
 
 impl Service {
+    /// Env var `Config::parse` falls back to for `--api-key`. `Local`
+    /// doesn't need a key, so it gets an empty name that no env var matches.
     pub fn get_env_var_name(&self) -> &'static str {
         match self {
             Service::Replicate => "REPLICATE_API_TOKEN",
             Service::ElevenLabs => "ELEVENLABS_API_KEY",
+            Service::Deepgram => "DEEPGRAM_API_KEY",
             Service::OpenAI => "OPENAI_API_KEY",
+            Service::Local => "",
         }
     }
 }
@@ -80,6 +225,38 @@ pub enum OutputType {
     Stdout,
 }
 
+#[derive(Debug, Clone, Copy, ValueEnum, PartialEq, Eq)]
+pub enum AudioFormat {
+    Int16,
+    Int24,
+    Float32,
+    Flac,
+}
+
+impl AudioFormat {
+    /// File extension matching this encoding, for the scratch file's name.
+    pub fn file_extension(&self) -> &'static str {
+        match self {
+            AudioFormat::Int16 | AudioFormat::Int24 | AudioFormat::Float32 => "wav",
+            AudioFormat::Flac => "flac",
+        }
+    }
+
+    /// MIME type to advertise when uploading the scratch file.
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            AudioFormat::Int16 | AudioFormat::Int24 | AudioFormat::Float32 => "audio/wav",
+            AudioFormat::Flac => "audio/flac",
+        }
+    }
+}
+
+impl Default for AudioFormat {
+    fn default() -> Self {
+        AudioFormat::Int16 // Matches the previous hardcoded behavior
+    }
+}
+
 // Implement logic to populate api_key after parsing args
 impl Config {
     pub fn parse() -> Self {
@@ -0,0 +1,150 @@
+//! Dedicated-thread audio controller: the sole owner of an `AudioRecorder`,
+//! driven purely by message passing so recording state lives in exactly one
+//! place instead of behind a shared `Mutex` that the main loop locks,
+//! toggles, drops, and then calls a blocking `recorder.start()`/`stop()`
+//! against (which can panic the whole program on `.unwrap()`). `cpal::Stream`
+//! isn't reliably portable across an await point on every platform, so —
+//! the same way `sound.rs` confines `rodio::OutputStream` — the recorder is
+//! moved onto its own thread and only ever touched from there; callers just
+//! send commands and poll a status stream.
+
+use crate::audio::{AudioRecorder, StreamChunk};
+use cpal::StreamConfig;
+use log::warn;
+use tokio::sync::mpsc as tokio_mpsc;
+
+/// Commands accepted by the controller thread. `StartRecording`/
+/// `StopRecording`/`Query` map directly onto a hotkey press or a status
+/// query; `StartStreaming` is an addition `--stream` mode needs now that
+/// `AudioRecorder` lives entirely inside this task instead of being
+/// reachable from the main loop.
+pub enum ControllerCommand {
+    StartRecording,
+    StartStreaming(crossbeam_channel::Sender<StreamChunk>),
+    StopRecording,
+    Query,
+}
+
+/// Status pushed back out of the controller thread as things actually
+/// happen, so the main loop never blocks waiting for a recorder call to
+/// return.
+pub enum ControllerStatus {
+    Recording,
+    Stopped,
+    SamplesReady {
+        stream_config: StreamConfig,
+        samples: Vec<f32>,
+    },
+    Error(String),
+}
+
+/// Handle to the running controller thread. Cheaply `Clone`-able so it can
+/// be handed to more than one command source (the main loop, the control
+/// socket) alongside the status stream.
+#[derive(Clone)]
+pub struct AudioController {
+    command_tx: crossbeam_channel::Sender<ControllerCommand>,
+}
+
+impl AudioController {
+    /// Spawns the controller thread, which takes ownership of `recorder`
+    /// for the rest of the process's life. Returns the handle plus the
+    /// status stream the main loop should poll alongside `hotkey_rx`.
+    ///
+    /// The thread waits on two channels via `crossbeam_channel::select!`:
+    /// the command channel driven by hotkeys/the control socket, and an
+    /// auto-stop channel `recorder` pushes to on its own (VAD silence or
+    /// `max_time`), from inside the cpal callback. Either one stops the
+    /// recorder and reports the same `ControllerStatus` a caller would get
+    /// from an explicit `StopRecording`, so the main loop doesn't need to
+    /// know which one fired.
+    pub fn spawn(mut recorder: AudioRecorder) -> (Self, tokio_mpsc::UnboundedReceiver<ControllerStatus>) {
+        let (command_tx, command_rx) = crossbeam_channel::unbounded::<ControllerCommand>();
+        let (auto_stop_tx, auto_stop_rx) = crossbeam_channel::unbounded::<()>();
+        let (status_tx, status_rx) = tokio_mpsc::unbounded_channel::<ControllerStatus>();
+
+        recorder.set_auto_stop_notifier(auto_stop_tx);
+
+        std::thread::Builder::new()
+            .name("audio-controller".to_string())
+            .spawn(move || {
+                loop {
+                    let status = crossbeam_channel::select! {
+                        recv(command_rx) -> command => {
+                            let Ok(command) = command else { break; };
+                            match command {
+                                ControllerCommand::StartRecording => match recorder.start() {
+                                    Ok(()) => ControllerStatus::Recording,
+                                    Err(e) => ControllerStatus::Error(e.to_string()),
+                                },
+                                ControllerCommand::StartStreaming(chunk_tx) => match recorder.start_streaming(chunk_tx) {
+                                    Ok(()) => ControllerStatus::Recording,
+                                    Err(e) => ControllerStatus::Error(e.to_string()),
+                                },
+                                ControllerCommand::StopRecording => match recorder.stop() {
+                                    Ok(Some((stream_config, samples))) => {
+                                        ControllerStatus::SamplesReady { stream_config, samples }
+                                    }
+                                    Ok(None) => ControllerStatus::Stopped,
+                                    Err(e) => ControllerStatus::Error(e.to_string()),
+                                },
+                                ControllerCommand::Query => {
+                                    if recorder.is_recording() {
+                                        ControllerStatus::Recording
+                                    } else {
+                                        ControllerStatus::Stopped
+                                    }
+                                }
+                            }
+                        }
+                        recv(auto_stop_rx) -> msg => {
+                            if msg.is_err() {
+                                // `recorder` (and the sender it owns) only
+                                // goes away with this thread, so this
+                                // shouldn't happen; nothing to report.
+                                continue;
+                            }
+                            match recorder.stop() {
+                                Ok(Some((stream_config, samples))) => {
+                                    ControllerStatus::SamplesReady { stream_config, samples }
+                                }
+                                Ok(None) => ControllerStatus::Stopped,
+                                Err(e) => ControllerStatus::Error(e.to_string()),
+                            }
+                        }
+                    };
+                    // If the main loop is gone there's nowhere left to report
+                    // to, but keep draining commands so a lingering sender
+                    // (e.g. the control socket) doesn't see a broken pipe.
+                    let _ = status_tx.send(status);
+                }
+            })
+            .expect("Failed to spawn the audio controller thread");
+
+        (Self { command_tx }, status_rx)
+    }
+
+    /// Queues a command. Never blocks; if the controller thread is gone this
+    /// just logs and drops it, matching `sound::SoundCues::play`.
+    fn send(&self, command: ControllerCommand) {
+        if self.command_tx.send(command).is_err() {
+            warn!("Audio controller thread is gone; dropping a command");
+        }
+    }
+
+    pub fn start_recording(&self) {
+        self.send(ControllerCommand::StartRecording);
+    }
+
+    pub fn start_streaming(&self, chunk_tx: crossbeam_channel::Sender<StreamChunk>) {
+        self.send(ControllerCommand::StartStreaming(chunk_tx));
+    }
+
+    pub fn stop_recording(&self) {
+        self.send(ControllerCommand::StopRecording);
+    }
+
+    pub fn query(&self) {
+        self.send(ControllerCommand::Query);
+    }
+}
@@ -0,0 +1,255 @@
+//! C-compatible FFI boundary for the dictation engine, in the
+//! flutter_rust_bridge style: only plain structs/enums cross the boundary,
+//! and asynchronous transcription results are delivered by polling a
+//! stream of events rather than returning them from a blocking call.
+//!
+//! A caller on the other side of this boundary (Dart, Swift, whatever) owns
+//! no Rust types directly: it gets an opaque `*mut Dictation` handle from
+//! `dictation_create`, drives it with the other `dictation_*` functions,
+//! and must eventually call `dictation_destroy`.
+
+use crate::config::{AudioFormat, Config, OutputType, Service};
+use crate::{Dictation, TranscriptEvent};
+use anyhow::{Context, Result};
+use std::ffi::{CStr, CString};
+use std::os::raw::c_char;
+use std::path::PathBuf;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub enum CService {
+    OpenAi = 0,
+    Replicate = 1,
+    ElevenLabs = 2,
+    Deepgram = 3,
+    Local = 4,
+}
+
+impl From<CService> for Service {
+    fn from(service: CService) -> Self {
+        match service {
+            CService::OpenAi => Service::OpenAI,
+            CService::Replicate => Service::Replicate,
+            CService::ElevenLabs => Service::ElevenLabs,
+            CService::Deepgram => Service::Deepgram,
+            CService::Local => Service::Local,
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub enum COutputType {
+    Clipboard = 0,
+    Paste = 1,
+    File = 2,
+    Stdout = 3,
+}
+
+impl From<COutputType> for OutputType {
+    fn from(output: COutputType) -> Self {
+        match output {
+            COutputType::Clipboard => OutputType::Clipboard,
+            COutputType::Paste => OutputType::Paste,
+            COutputType::File => OutputType::File,
+            COutputType::Stdout => OutputType::Stdout,
+        }
+    }
+}
+
+/// One polled transcription result. `text` is an owned, NUL-terminated
+/// string that must be freed with `dictation_free_string`.
+#[repr(C)]
+pub struct CTranscriptEvent {
+    pub sequence: u64,
+    pub is_error: bool,
+    pub text: *mut c_char,
+}
+
+/// Reads an optional, caller-owned C string argument. `ptr` may be null to
+/// mean "unset"; otherwise it must point at a valid NUL-terminated string
+/// for the duration of this call.
+unsafe fn opt_cstr(ptr: *const c_char) -> Result<Option<String>> {
+    if ptr.is_null() {
+        return Ok(None);
+    }
+    Ok(Some(
+        CStr::from_ptr(ptr)
+            .to_str()
+            .context("Invalid UTF-8 in FFI string argument")?
+            .to_string(),
+    ))
+}
+
+fn build_config(
+    service: CService,
+    output: COutputType,
+    api_key: *const c_char,
+    model_path: *const c_char,
+    language: *const c_char,
+    max_time_seconds: u32,
+) -> Result<Config> {
+    // SAFETY: the three string pointers are documented caller obligations
+    // on `dictation_create`, the only function that calls this.
+    let api_key = unsafe { opt_cstr(api_key) }?.unwrap_or_default();
+    let model = unsafe { opt_cstr(model_path) }?.map(PathBuf::from);
+    let language = unsafe { opt_cstr(language) }?;
+
+    Ok(Config {
+        api_key_arg: None,
+        service: service.into(),
+        output: output.into(),
+        file: None,
+        modifier: "Control".to_string(),
+        key: "F11".to_string(),
+        hotkey_config: None,
+        grab: false,
+        ptt: false,
+        retries: 3,
+        max_time: max_time_seconds,
+        device: None,
+        list_devices: false,
+        vad: true,
+        vad_silence_ms: 800,
+        vad_threshold_multiplier: 2.0,
+        stream: false,
+        audio_format: AudioFormat::default(),
+        model,
+        language,
+        base_url: None,
+        model_name: None,
+        #[cfg(feature = "metrics")]
+        metrics_addr: None,
+        #[cfg(feature = "metrics")]
+        metrics_pushgateway: None,
+        compress_cache: false,
+        max_cache_bytes: None,
+        sound: false,
+        notify: false,
+        toggle: false,
+        api_key,
+    })
+}
+
+/// Builds a `Dictation` engine from plain C-compatible arguments. `api_key`,
+/// `model_path` and `language` may be null to mean "unset". Returns null on
+/// failure; check logs (`RUST_LOG`) for the reason.
+#[no_mangle]
+pub extern "C" fn dictation_create(
+    service: CService,
+    output: COutputType,
+    api_key: *const c_char,
+    model_path: *const c_char,
+    language: *const c_char,
+    max_time_seconds: u32,
+) -> *mut Dictation {
+    let config = match build_config(service, output, api_key, model_path, language, max_time_seconds) {
+        Ok(config) => config,
+        Err(e) => {
+            log::error!("dictation_create: {}", e);
+            return std::ptr::null_mut();
+        }
+    };
+    match Dictation::new(config) {
+        Ok(dictation) => Box::into_raw(Box::new(dictation)),
+        Err(e) => {
+            log::error!("dictation_create: {}", e);
+            std::ptr::null_mut()
+        }
+    }
+}
+
+/// # Safety
+/// `handle` must be a live pointer returned by `dictation_create` and not
+/// yet passed to `dictation_destroy`.
+#[no_mangle]
+pub extern "C" fn dictation_start(handle: *mut Dictation) -> bool {
+    let Some(dictation) = (unsafe { handle.as_mut() }) else {
+        return false;
+    };
+    match dictation.start() {
+        Ok(()) => true,
+        Err(e) => {
+            log::error!("dictation_start: {}", e);
+            false
+        }
+    }
+}
+
+/// # Safety
+/// `handle` must be a live pointer returned by `dictation_create` and not
+/// yet passed to `dictation_destroy`.
+#[no_mangle]
+pub extern "C" fn dictation_stop(handle: *mut Dictation) -> bool {
+    let Some(dictation) = (unsafe { handle.as_mut() }) else {
+        return false;
+    };
+    match dictation.stop() {
+        Ok(()) => true,
+        Err(e) => {
+            log::error!("dictation_stop: {}", e);
+            false
+        }
+    }
+}
+
+/// # Safety
+/// `handle` must be a live pointer returned by `dictation_create` and not
+/// yet passed to `dictation_destroy`.
+#[no_mangle]
+pub extern "C" fn dictation_is_recording(handle: *const Dictation) -> bool {
+    unsafe { handle.as_ref() }.map(|d| d.is_recording()).unwrap_or(false)
+}
+
+/// Non-blocking poll for the next finished transcription. Returns `false`
+/// (and leaves `out` untouched) if nothing is ready yet; the host should
+/// call this periodically — e.g. once per UI frame — rather than block on
+/// it, since there is no callback/wakeup on the Rust side.
+///
+/// # Safety
+/// `handle` must be a live pointer returned by `dictation_create`, and
+/// `out` must point at a valid, writable `CTranscriptEvent`.
+#[no_mangle]
+pub extern "C" fn dictation_poll_event(handle: *mut Dictation, out: *mut CTranscriptEvent) -> bool {
+    let Some(dictation) = (unsafe { handle.as_mut() }) else {
+        return false;
+    };
+    let Some(event) = dictation.try_recv_event() else {
+        return false;
+    };
+    let (sequence, is_error, text) = match event {
+        TranscriptEvent::Text { sequence, text } => (sequence, false, text),
+        TranscriptEvent::Error { sequence, message } => (sequence, true, message),
+    };
+    let c_text = CString::new(text).unwrap_or_else(|_| CString::new("<transcription contained a NUL byte>").unwrap());
+    unsafe {
+        (*out).sequence = sequence;
+        (*out).is_error = is_error;
+        (*out).text = c_text.into_raw();
+    }
+    true
+}
+
+/// Frees a string previously handed back via `CTranscriptEvent::text`.
+///
+/// # Safety
+/// `s` must either be null or a pointer previously returned in a
+/// `CTranscriptEvent`, not already freed.
+#[no_mangle]
+pub extern "C" fn dictation_free_string(s: *mut c_char) {
+    if !s.is_null() {
+        unsafe { drop(CString::from_raw(s)) };
+    }
+}
+
+/// Destroys the engine, stopping any in-progress recording first.
+///
+/// # Safety
+/// `handle` must be a pointer returned by `dictation_create`, not already
+/// passed to this function.
+#[no_mangle]
+pub extern "C" fn dictation_destroy(handle: *mut Dictation) {
+    if !handle.is_null() {
+        unsafe { drop(Box::from_raw(handle)) };
+    }
+}
@@ -1,27 +1,60 @@
-use crate::config::Config;
-use anyhow::{anyhow, Result};
+use crate::bindings::{self, Hotkey, Modifier};
+use crate::config::{Config, OutputType, Service};
+use anyhow::{anyhow, Context, Result};
 use log::{debug, error, info, warn};
 use rdev::{listen, Event, EventType, Key};
 // Use std mpsc for sync listener thread
 use crate::utils::is_wayland;
+use std::collections::HashSet;
 use std::sync::{Arc, Mutex};
 use tokio::sync::mpsc::Sender; // Use tokio mpsc for sending to async main loop
+use tokio::sync::oneshot;
 
 #[cfg(feature = "wayland")]
 use evdev::{Device, KeyCode};
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum HotkeyEvent {
     ToggleRecording,
+    PushToTalkStart,
+    PushToTalkStop,
+    CancelRecording,
+    SwitchService(Service),
+    ChangeOutput(OutputType),
+    /// Switches the active binding set; handled inside the listener and
+    /// never forwarded to the main loop.
+    SwitchMode(String),
 }
 
+/// Name of the binding set that's active until a `switch_mode` binding fires.
+const DEFAULT_MODE: &str = "default";
+
 // --- Platform Agnostic Listener ---
-pub fn listen_for_hotkeys(config: Arc<Config>, tx: Sender<HotkeyEvent>) -> Result<()> {
+/// Blocks forever pumping hotkey events into `tx`. `ready_tx` is fired once
+/// the binding table is loaded and this is about to hand off to the
+/// platform-specific listener — the point after which `main`'s startup
+/// handshake can treat the hotkey subsystem as up, so a `--toggle` client
+/// launched right after the daemon can't race an uninitialized listener.
+pub fn listen_for_hotkeys(config: Arc<Config>, tx: Sender<HotkeyEvent>, ready_tx: oneshot::Sender<()>) -> Result<()> {
+    let bindings = Arc::new(load_binding_table(&config)?);
+    info!(
+        "Loaded {} hotkey binding(s){}",
+        bindings.len(),
+        config
+            .hotkey_config
+            .as_ref()
+            .map(|p| format!(" from {}", p.display()))
+            .unwrap_or_else(|| " (legacy --modifier/--key flags)".to_string())
+    );
+    // The receiver may already be gone if main's readiness wait timed out;
+    // that's fine, there's no one left to tell.
+    let _ = ready_tx.send(());
+
     if cfg!(target_os = "linux") && is_wayland() {
         info!("Wayland detected. Attempting evdev-based listener.");
         #[cfg(feature = "wayland")]
         {
-            listen_wayland(config, tx)
+            listen_wayland(bindings, tx)
         }
         #[cfg(not(feature = "wayland"))]
         {
@@ -29,28 +62,46 @@ pub fn listen_for_hotkeys(config: Arc<Config>, tx: Sender<HotkeyEvent>) -> Resul
         }
     } else {
         info!("Using rdev listener (X11/Windows/macOS).");
-        listen_rdev(config, tx)
+        listen_rdev(bindings, tx)
     }
 }
 
-// --- rdev Listener (X11, Windows, macOS) ---
-fn listen_rdev(config: Arc<Config>, tx: Sender<HotkeyEvent>) -> Result<()> {
-    // Map config strings to rdev keys (needs careful parsing)
-    let target_modifier = parse_modifier_rdev(&config.modifier)?;
-    let target_key = parse_key_rdev(&config.key)?;
-    info!(
-        "rdev: Listening for Modifier: {:?}, Key: {:?}",
-        target_modifier, target_key
-    );
+fn load_binding_table(config: &Config) -> Result<Vec<Hotkey>> {
+    match &config.hotkey_config {
+        Some(path) => bindings::load_bindings(path),
+        None => bindings::default_bindings(&config.modifier, &config.key, config.grab, config.ptt),
+    }
+}
 
+fn mode_matches(binding_mode: &Option<String>, current_mode: &str) -> bool {
+    binding_mode
+        .as_deref()
+        .map_or(true, |m| m == current_mode)
+}
+
+/// Whether every modifier the chord requires (e.g. `Ctrl+Alt`) is currently
+/// held. Left/right variants of the same logical modifier are equivalent,
+/// since `held` is built from `rdev_key_to_modifier`/`evdev_key_to_modifier`.
+fn modifiers_satisfied(required: &[Modifier], held: &HashSet<Modifier>) -> bool {
+    required.iter().all(|m| held.contains(m))
+}
+
+// --- rdev Listener (X11, Windows, macOS) ---
+fn listen_rdev(bindings: Arc<Vec<Hotkey>>, tx: Sender<HotkeyEvent>) -> Result<()> {
     struct State {
-        mod_pressed: bool,
-        key_pressed: bool,
+        held_modifiers: HashSet<Modifier>,
+        pressed_keys: HashSet<Key>,
+        mode: String,
+        /// Key whose press matched a `PushToTalkStart` binding, so we know
+        /// which release should fire the matching `PushToTalkStop`.
+        active_ptt_key: Option<Key>,
     }
 
     let state = Arc::new(Mutex::new(State {
-        mod_pressed: false,
-        key_pressed: false,
+        held_modifiers: HashSet::new(),
+        pressed_keys: HashSet::new(),
+        mode: DEFAULT_MODE.to_string(),
+        active_ptt_key: None,
     }));
 
     let (async_tx, mut async_rx) = tokio::sync::mpsc::unbounded_channel::<HotkeyEvent>();
@@ -73,56 +124,59 @@ fn listen_rdev(config: Arc<Config>, tx: Sender<HotkeyEvent>) -> Result<()> {
     while crate::IS_RUNNING.load(std::sync::atomic::Ordering::Relaxed) {
         let state_copy = state.clone();
         let async_tx_copy = async_tx.clone();
-        let callback = move |event: Event| {
-            match event.event_type {
-                EventType::KeyPress(key) => {
-                    let mut state_val = state_copy.lock().unwrap();
-                    // Check modifier press
-                    if key == target_modifier.0 || key == target_modifier.1 {
-                        state_val.mod_pressed = true;
-                        state_val.key_pressed = false;
-                        // Reset key pressed state if modifier is re-pressed
-                        debug!("rdev: Modifier {:?} pressed", key);
+        let bindings_copy = bindings.clone();
+        let callback = move |event: Event| match event.event_type {
+            EventType::KeyPress(key) => {
+                let mut state_val = state_copy.lock().unwrap();
+                if let Some(m) = rdev_key_to_modifier(key) {
+                    state_val.held_modifiers.insert(m);
+                    debug!("rdev: Modifier {:?} pressed", key);
+                    return;
+                }
+
+                // Key-repeat guard: only fire on the press transition.
+                if state_val.pressed_keys.contains(&key) {
+                    return;
+                }
+                state_val.pressed_keys.insert(key);
+
+                for binding in bindings_copy.iter() {
+                    if !mode_matches(&binding.mode, &state_val.mode) {
+                        continue;
                     }
-                    // Check target key press ONLY if modifier is ALREADY held
-                    else if key == target_key {
-                        // Only trigger if main key wasn't already down AND modifier is down
-                        if !state_val.key_pressed && state_val.mod_pressed {
-                            debug!("rdev: Target key {:?} pressed with modifier held", key);
-                            // Send toggle event
-                            if async_tx_copy.send(HotkeyEvent::ToggleRecording).is_err() {
-                                error!("rdev: Failed to send toggle event from callback.");
-                                // Consider how to signal failure or stop listening
-                            }
-                            state_val.key_pressed = true;
-                        } else if state_val.key_pressed {
-                            debug!("rdev: Target key {:?} already held down.", key);
-                        } else {
-                            debug!("rdev: Target key {:?} pressed WITHOUT modifier held.", key);
+                    let target = match parse_key_rdev(&binding.key) {
+                        Ok(k) => k,
+                        Err(_) => continue,
+                    };
+                    if key == target
+                        && modifiers_satisfied(&binding.modifiers, &state_val.held_modifiers)
+                    {
+                        debug!("rdev: Matched binding {:?}", binding);
+                        if binding.event == HotkeyEvent::PushToTalkStart {
+                            state_val.active_ptt_key = Some(key);
                         }
-                    } else {
-                        // Another key pressed, ignore for hotkey logic but could log
-                        // debug!("rdev: Other key pressed: {:?}", key);
+                        let mode = &mut state_val.mode;
+                        dispatch(&async_tx_copy, mode, &binding.event);
                     }
                 }
-                EventType::KeyRelease(key) => {
-                    let mut state_val = state_copy.lock().unwrap();
-                    // Check modifier release
-                    if key == target_modifier.0 || key == target_modifier.1 {
-                        state_val.mod_pressed = false;
-                        state_val.key_pressed = false;
-                        debug!("rdev: Modifier {:?} released", key);
-                    }
-                    // Check target key release
-                    else if key == target_key {
-                        state_val.key_pressed = false;
-                        debug!("rdev: Target key {:?} released", key);
-                    } else {
-                        // Other key released
+            }
+            EventType::KeyRelease(key) => {
+                let mut state_val = state_copy.lock().unwrap();
+                if let Some(m) = rdev_key_to_modifier(key) {
+                    state_val.held_modifiers.remove(&m);
+                    debug!("rdev: Modifier {:?} released", key);
+                }
+                state_val.pressed_keys.remove(&key);
+
+                if state_val.active_ptt_key == Some(key) {
+                    state_val.active_ptt_key = None;
+                    debug!("rdev: Push-to-talk key released, stopping recording");
+                    if async_tx_copy.send(HotkeyEvent::PushToTalkStop).is_err() {
+                        error!("Failed to send push-to-talk stop event from callback.");
                     }
                 }
-                _ => (), // Ignore mouse/other events
             }
+            _ => (), // Ignore mouse/other events
         };
         if let Err(e) = listen(callback) {
             error!("rdev error: {:?}", e);
@@ -138,104 +192,341 @@ fn listen_rdev(config: Arc<Config>, tx: Sender<HotkeyEvent>) -> Result<()> {
     Ok(())
 }
 
+/// Sends a matched action to the main loop, except `SwitchMode` which only
+/// mutates the listener's own active-mode state.
+fn dispatch(tx: &tokio::sync::mpsc::UnboundedSender<HotkeyEvent>, mode: &mut String, event: &HotkeyEvent) {
+    if let HotkeyEvent::SwitchMode(new_mode) = event {
+        info!("hotkey: switching binding mode to '{}'", new_mode);
+        *mode = new_mode.clone();
+        return;
+    }
+    if tx.send(event.clone()).is_err() {
+        error!("Failed to send hotkey event from callback.");
+    }
+}
+
 // --- Wayland / evdev Listener (Linux Only) ---
+//
+// Single-threaded epoll loop: one fd per keyboard, no per-device task and no
+// inner tokio runtime. `tx.blocking_send` is safe here because
+// `listen_for_hotkeys` always runs inside `spawn_blocking` (see main.rs).
 #[cfg(feature = "wayland")]
-fn listen_wayland(config: Arc<Config>, tx: Sender<HotkeyEvent>) -> Result<()> {
+fn listen_wayland(bindings: Arc<Vec<Hotkey>>, tx: Sender<HotkeyEvent>) -> Result<()> {
+    use epoll::Events;
     use evdev::{EventType, KeyCode};
-    use std::collections::{HashMap, HashSet};
-
-    // Keys we care about ------------------------------------------------------
-    let target_mods = parse_modifier_evdev(&config.modifier)?;
-    let target_key = parse_key_evdev(&config.key)?;
+    use inotify::{Inotify, WatchMask};
+    use std::collections::HashMap;
+    use std::os::unix::io::AsRawFd;
+
+    // When any binding wants to consume its chord, we grab the source
+    // devices outright (so matched keys never reach the focused app) and
+    // re-inject everything else through a uinput virtual device, the same
+    // pattern sohkd uses for its `consume` option.
+    let mut consuming = bindings.iter().any(|b| b.consume);
+
+    let key_resolver = match crate::xkb::KeyResolver::from_system_rmlvo() {
+        Ok(r) => Some(r),
+        Err(e) => {
+            warn!(
+                "evdev: failed to build XKB key resolver, falling back to the built-in key table: {}",
+                e
+            );
+            None
+        }
+    };
 
-    info!("evdev: listening for mods={target_mods:?}, key={target_key:?}");
+    let mut devices = HashMap::new();
+    scan_keyboards(&mut devices).context("Failed to scan keyboards")?;
 
-    // One lightweight runtime -------------------------------------------------
-    let rt = tokio::runtime::Builder::new_current_thread()
-        .enable_io()
-        .build()?;
+    let mut passthrough = if consuming {
+        match build_passthrough_device() {
+            Ok(dev) => Some(dev),
+            Err(e) => {
+                error!(
+                    "evdev: failed to create uinput passthrough device, disabling consume mode: {}",
+                    e
+                );
+                consuming = false;
+                None
+            }
+        }
+    } else {
+        None
+    };
 
-    rt.block_on(async move {
-        // Discover keyboards once at start; we’ll refresh on udev events later.
-        let mut devices = HashMap::new();
-        if let Err(e) = scan_keyboards(&mut devices) {
-            error!("Failed to scan keyboards: {}", e);
-            return;
+    let epfd = epoll::create(false).context("Failed to create epoll instance")?;
+    // fd -> (path, Device), looked up whenever epoll reports that fd is readable.
+    let mut fd_devices: HashMap<i32, (PathBuf, Device)> = HashMap::new();
+    for (path, device) in devices.drain() {
+        if let Err(e) = register_device(epfd, &mut fd_devices, path.clone(), device, consuming) {
+            warn!("evdev: failed to register {}: {}", path.display(), e);
         }
+    }
+    info!("evdev: polling {} keyboard device(s) via epoll", fd_devices.len());
+
+    // Watch /dev/input for USB/dock keyboard hot-plug so devices attached
+    // after startup are picked up without restarting the listener.
+    let mut inotify = Inotify::init().context("Failed to initialize inotify")?;
+    inotify
+        .watches()
+        .add("/dev/input", WatchMask::CREATE | WatchMask::DELETE)
+        .context("Failed to watch /dev/input for hot-plug events")?;
+    let inotify_fd = inotify.as_raw_fd();
+    epoll::ctl(
+        epfd,
+        epoll::ControlOptions::EPOLL_CTL_ADD,
+        inotify_fd,
+        epoll::Event::new(Events::EPOLLIN, inotify_fd as u64),
+    )
+    .context("Failed to register inotify watch with epoll")?;
+
+    // Modifier/mode bookkeeping --------------------------------------------
+    let mut held_modifiers: HashSet<Modifier> = HashSet::new();
+    let mut mode = DEFAULT_MODE.to_string();
+    // Keys whose press we swallowed, so their matching release is
+    // swallowed too instead of leaking a "key down forever" to the focus.
+    let mut swallowed_keys: HashSet<KeyCode> = HashSet::new();
+    // Keys currently down, so autorepeat (evdev value == 2) doesn't
+    // re-match bindings on every repeat tick.
+    let mut pressed_keys: HashSet<KeyCode> = HashSet::new();
+    // Key whose press matched a `PushToTalkStart` binding, so we know which
+    // release should fire the matching `PushToTalkStop`.
+    let mut active_ptt_key: Option<KeyCode> = None;
+    let mut inotify_buf = [0u8; 4096];
+
+    let mut epoll_events = vec![epoll::Event::new(Events::empty(), 0); 32];
+    while crate::IS_RUNNING.load(std::sync::atomic::Ordering::Relaxed) {
+        // Finite timeout so we periodically re-check IS_RUNNING for shutdown.
+        let ready = match epoll::wait(epfd, 250, &mut epoll_events) {
+            Ok(n) => n,
+            Err(e) => {
+                error!("evdev: epoll_wait failed: {}", e);
+                break;
+            }
+        };
+
+        for epoll_event in &epoll_events[..ready] {
+            let fd = epoll_event.data as i32;
 
-        // Channel every device will write into
-        let (evt_tx, mut evt_rx) = tokio::sync::mpsc::unbounded_channel::<evdev::InputEvent>();
+            if fd == inotify_fd {
+                let events = match inotify.read_events(&mut inotify_buf) {
+                    Ok(evs) => evs,
+                    Err(e) => {
+                        warn!("evdev: failed to read inotify events: {}", e);
+                        continue;
+                    }
+                };
+                for event in events {
+                    let Some(name) = event.name.and_then(|n| n.to_str()) else {
+                        continue;
+                    };
+                    if !name.starts_with("event") {
+                        continue; // Only care about /dev/input/eventN nodes.
+                    }
+                    let path = PathBuf::from("/dev/input").join(name);
+
+                    if event.mask.contains(inotify::EventMask::DELETE) {
+                        if let Some((fd, _)) = fd_devices.iter().find(|(_, (p, _))| p == &path).map(|(fd, v)| (*fd, v)) {
+                            let _ = epoll::ctl(epfd, epoll::ControlOptions::EPOLL_CTL_DEL, fd, epoll::Event::new(Events::empty(), 0));
+                            fd_devices.remove(&fd);
+                            info!("evdev: hot-unplugged keyboard {}", path.display());
+                        }
+                        continue;
+                    }
 
-        // Spawn one async task per device
-        for dev in devices.into_values() {
-            let stream = match dev.into_event_stream() {
-                Ok(s) => s,
+                    if event.mask.contains(inotify::EventMask::CREATE) {
+                        if let Some(device) = try_open_keyboard(&path) {
+                            match register_device(epfd, &mut fd_devices, path.clone(), device, consuming) {
+                                Ok(_) => info!("evdev: hot-plugged keyboard {}", path.display()),
+                                Err(e) => warn!("evdev: failed to register hot-plugged device {}: {}", path.display(), e),
+                            }
+                        }
+                    }
+                }
+                continue;
+            }
+
+            let Some((path, device)) = fd_devices.get_mut(&fd) else {
+                continue;
+            };
+            let events = match device.fetch_events() {
+                Ok(evs) => evs,
                 Err(e) => {
-                    error!("Failed to create event stream: {}", e);
+                    warn!("evdev: failed to fetch events from {}: {}", path.display(), e);
                     continue;
                 }
             };
-            let mut stream = stream;
-            let evt_tx = evt_tx.clone();
-            tokio::spawn(async move {
-                loop {
-                    match stream.next_event().await {
-                        Ok(ev) => {
-                            if evt_tx.send(ev).is_err() {
-                                break;
-                            }
-                        }
-                        Err(e) => {
-                            error!("evdev: stream error: {e}");
-                            break;
+
+            for ev in events {
+                if ev.event_type() != EventType::KEY {
+                    forward(&mut passthrough, ev);
+                    continue;
+                }
+                let key: KeyCode = KeyCode::new(ev.code());
+                let pressed = ev.value() != 0;
+
+                if let Some(m) = evdev_key_to_modifier(key) {
+                    if pressed {
+                        held_modifiers.insert(m);
+                    } else {
+                        held_modifiers.remove(&m);
+                    }
+                    forward(&mut passthrough, ev);
+                    continue;
+                }
+
+                if !pressed {
+                    pressed_keys.remove(&key);
+                    if active_ptt_key == Some(key) {
+                        active_ptt_key = None;
+                        debug!("evdev: Push-to-talk key released, stopping recording");
+                        if tx.blocking_send(HotkeyEvent::PushToTalkStop).is_err() {
+                            warn!("evdev: Hotkey receiver dropped – stopping listener.");
+                            return Ok(());
                         }
                     }
+                    if swallowed_keys.remove(&key) {
+                        continue; // Swallow the release that matches a swallowed press.
+                    }
+                    forward(&mut passthrough, ev);
+                    continue;
                 }
-            });
-        }
-        drop(evt_tx); // closes when all senders gone
 
-        // Modifier bookkeeping ------------------------------------------------
-        let mut pressed_keys: HashSet<KeyCode> = HashSet::new();
+                // Key-repeat guard: only match bindings on the press
+                // transition, not on every autorepeat (value == 2) tick.
+                if !pressed_keys.insert(key) {
+                    if swallowed_keys.contains(&key) {
+                        continue; // Already swallowed; keep swallowing repeats too.
+                    }
+                    forward(&mut passthrough, ev);
+                    continue;
+                }
 
-        while let Some(ev) = evt_rx.recv().await {
-            // Only care about key events
-            if ev.event_type() != EventType::KEY {
-                continue;
-            }
-            let key: KeyCode = KeyCode::new(ev.code());
-            let pressed = ev.value() != 0;
+                let mut matched_consuming = false;
+                for binding in bindings.iter() {
+                    if !mode_matches(&binding.mode, &mode) {
+                        continue;
+                    }
+                    let target = match parse_key_evdev(&binding.key, key_resolver.as_ref()) {
+                        Ok(k) => k,
+                        Err(_) => continue,
+                    };
+                    if key == target && modifiers_satisfied(&binding.modifiers, &held_modifiers) {
+                        if let HotkeyEvent::SwitchMode(new_mode) = &binding.event {
+                            info!("hotkey: switching binding mode to '{}'", new_mode);
+                            mode = new_mode.clone();
+                            matched_consuming |= binding.consume;
+                            continue;
+                        }
+                        if binding.event == HotkeyEvent::PushToTalkStart {
+                            active_ptt_key = Some(key);
+                        }
+                        if tx.blocking_send(binding.event.clone()).is_err() {
+                            warn!("evdev: Hotkey receiver dropped – stopping listener.");
+                            return Ok(());
+                        }
+                        matched_consuming |= binding.consume;
+                    }
+                }
 
-            if pressed {
-                pressed_keys.insert(key);
-            } else {
-                pressed_keys.remove(&key);
-            }
-            if key == target_key
-                && pressed
-                && (pressed_keys.contains(&target_mods.0) || pressed_keys.contains(&target_mods.1))
-            {
-                if let Err(e) = tx.send(HotkeyEvent::ToggleRecording).await {
-                    warn!("evdev: Hotkey receiver dropped – stopping listener: {}", e);
-                    break;
+                if matched_consuming {
+                    swallowed_keys.insert(key);
+                } else {
+                    forward(&mut passthrough, ev);
                 }
             }
         }
-    });
+    }
 
     info!("evdev listener finished");
     Ok(())
 }
 
+/// Grabs (if `consuming`) and registers a keyboard device with the epoll set,
+/// recording it in `fd_devices` so the poll loop can look it back up by fd.
+#[cfg(feature = "wayland")]
+fn register_device(
+    epfd: std::os::fd::RawFd,
+    fd_devices: &mut std::collections::HashMap<std::os::fd::RawFd, (PathBuf, Device)>,
+    path: PathBuf,
+    mut device: Device,
+    consuming: bool,
+) -> Result<()> {
+    use epoll::Events;
+    use std::os::unix::io::AsRawFd;
+
+    if consuming {
+        if let Err(e) = device.grab() {
+            warn!("evdev: failed to grab device {}: {}", path.display(), e);
+        }
+    }
+
+    let fd = device.as_raw_fd();
+    epoll::ctl(
+        epfd,
+        epoll::ControlOptions::EPOLL_CTL_ADD,
+        fd,
+        epoll::Event::new(Events::EPOLLIN, fd as u64),
+    )
+    .with_context(|| format!("Failed to register {} with epoll", path.display()))?;
+    fd_devices.insert(fd, (path, device));
+    Ok(())
+}
+
+/// Creates a uinput virtual keyboard that mirrors every key capability, used
+/// to re-emit events from grabbed devices that weren't consumed by a chord.
+#[cfg(feature = "wayland")]
+fn build_passthrough_device() -> Result<evdev::uinput::VirtualDevice> {
+    use evdev::uinput::VirtualDeviceBuilder;
+    use evdev::{AttributeSet, KeyCode};
+
+    let mut keys = AttributeSet::<KeyCode>::new();
+    for code in 0..KeyCode::KEY_MAX.code() {
+        keys.insert(KeyCode::new(code));
+    }
+
+    VirtualDeviceBuilder::new()?
+        .name("whisper-dictation-passthrough")
+        .with_keys(&keys)?
+        .build()
+        .map_err(|e| anyhow!("Failed to build uinput passthrough device: {}", e))
+}
+
+/// Re-emits a raw input event (plus the SYN_REPORT that terminates it) on the
+/// passthrough device, if consume mode is active.
+#[cfg(feature = "wayland")]
+fn forward(passthrough: &mut Option<evdev::uinput::VirtualDevice>, ev: evdev::InputEvent) {
+    use evdev::{EventType, InputEvent};
+
+    if let Some(vdev) = passthrough {
+        let syn = InputEvent::new(EventType::SYNCHRONIZATION.0, 0, 0);
+        if let Err(e) = vdev.emit(&[ev, syn]) {
+            warn!("evdev: failed to forward event through passthrough device: {}", e);
+        }
+    }
+}
+
 // --- Helper Functions ---
 
-fn parse_modifier_rdev(mod_str: &str) -> Result<(Key, Key)> {
-    match mod_str.to_lowercase().as_str() {
-        "ctrl" | "control" => Ok((Key::ControlLeft, Key::ControlRight)),
-        "alt" => Ok((Key::Alt, Key::AltGr)), // AltGr might be Right Alt
-        "shift" => Ok((Key::ShiftLeft, Key::ShiftRight)),
-        "meta" | "super" | "win" | "cmd" | "command" => Ok((Key::MetaLeft, Key::MetaRight)),
-        _ => Err(anyhow!("Unsupported rdev modifier string: {}", mod_str)),
+fn rdev_key_to_modifier(key: Key) -> Option<Modifier> {
+    match key {
+        Key::ControlLeft | Key::ControlRight => Some(Modifier::Ctrl),
+        Key::Alt | Key::AltGr => Some(Modifier::Alt),
+        Key::ShiftLeft | Key::ShiftRight => Some(Modifier::Shift),
+        Key::MetaLeft | Key::MetaRight => Some(Modifier::Meta),
+        _ => None,
+    }
+}
+
+#[cfg(feature = "wayland")]
+fn evdev_key_to_modifier(key: KeyCode) -> Option<Modifier> {
+    use evdev::KeyCode;
+    match key {
+        KeyCode::KEY_LEFTCTRL | KeyCode::KEY_RIGHTCTRL => Some(Modifier::Ctrl),
+        KeyCode::KEY_LEFTALT | KeyCode::KEY_RIGHTALT => Some(Modifier::Alt),
+        KeyCode::KEY_LEFTSHIFT | KeyCode::KEY_RIGHTSHIFT => Some(Modifier::Shift),
+        KeyCode::KEY_LEFTMETA | KeyCode::KEY_RIGHTMETA => Some(Modifier::Meta),
+        _ => None,
     }
 }
 
@@ -267,24 +558,22 @@ fn parse_key_rdev(key_str: &str) -> Result<Key> {
     }
 }
 
+/// Resolves a binding's key name to an evdev keycode. Tries the XKB
+/// resolver first (so layout-dependent symbols and dead keys work), falling
+/// back to the small hard-coded table below when no resolver is available
+/// or the name isn't a known XKB keysym.
 #[cfg(feature = "wayland")]
-fn parse_modifier_evdev(mod_str: &str) -> Result<(KeyCode, KeyCode)> {
+fn parse_key_evdev(key_str: &str, resolver: Option<&crate::xkb::KeyResolver>) -> Result<KeyCode> {
     use evdev::KeyCode;
-    let codes = match mod_str.to_lowercase().as_str() {
-        "ctrl" | "control" => (KeyCode::KEY_LEFTCTRL, KeyCode::KEY_RIGHTCTRL),
-        "alt" => (KeyCode::KEY_LEFTALT, KeyCode::KEY_RIGHTALT),
-        "shift" => (KeyCode::KEY_LEFTSHIFT, KeyCode::KEY_RIGHTSHIFT),
-        "meta" | "super" | "win" | "cmd" | "command" => {
-            (KeyCode::KEY_LEFTMETA, KeyCode::KEY_RIGHTMETA)
+
+    if let Some(resolver) = resolver {
+        if let Ok(codes) = resolver.resolve(key_str) {
+            if let Some(&code) = codes.first() {
+                return Ok(KeyCode::new(code as u16));
+            }
         }
-        _ => return Err(anyhow!("Unsupported evdev modifier string: {}", mod_str)),
-    };
-    Ok(codes)
-}
+    }
 
-#[cfg(feature = "wayland")]
-fn parse_key_evdev(key_str: &str) -> Result<KeyCode> {
-    use evdev::KeyCode;
     match key_str.to_lowercase().as_str() {
         "f1" => Ok(KeyCode::KEY_F1),
         "f2" => Ok(KeyCode::KEY_F2),
@@ -313,18 +602,18 @@ fn parse_key_evdev(key_str: &str) -> Result<KeyCode> {
 #[cfg(feature = "wayland")]
 use std::collections::HashMap;
 #[cfg(feature = "wayland")]
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 #[cfg(feature = "wayland")]
 fn scan_keyboards(devices: &mut HashMap<PathBuf, Device>) -> Result<()> {
-    use evdev::{enumerate, Device, EventType, KeyCode};
-    use std::collections::HashSet;
+    use evdev::{enumerate, Device};
+    use std::collections::HashSet as StdHashSet;
 
     // Get current device paths
-    let current_paths: HashSet<PathBuf> = enumerate().map(|(path, _)| path).collect();
+    let current_paths: StdHashSet<PathBuf> = enumerate().map(|(path, _)| path).collect();
 
     debug!("evdev: Found {} total input devices", current_paths.len());
 
-    let known_paths: HashSet<PathBuf> = devices.keys().cloned().collect();
+    let known_paths: StdHashSet<PathBuf> = devices.keys().cloned().collect();
 
     // Remove disconnected devices
     for path in known_paths.difference(&current_paths) {
@@ -334,56 +623,65 @@ fn scan_keyboards(devices: &mut HashMap<PathBuf, Device>) -> Result<()> {
 
     // Add new keyboard devices
     for path in current_paths.difference(&known_paths) {
-        debug!("evdev: Checking device: {}", path.display());
-        if let Ok(device) = Device::open(&path) {
-            // Check if it's a keyboard by looking for key events and typical keyboard keys
-            if device.supported_events().contains(EventType::KEY) {
-                let keys = device
-                    .supported_keys()
-                    .map(|keys| keys.into_iter().collect::<Vec<_>>())
-                    .unwrap_or_default();
-
-                // Check for typical keyboard keys
-                let has_keyboard_keys = keys.iter().any(|&key| {
-                    matches!(
-                        key,
-                        KeyCode::KEY_A
-                            | KeyCode::KEY_B
-                            | KeyCode::KEY_C
-                            | KeyCode::KEY_SPACE
-                            | KeyCode::KEY_ENTER
-                            | KeyCode::KEY_LEFTSHIFT
-                            | KeyCode::KEY_LEFTCTRL
-                    )
-                });
-
-                if has_keyboard_keys {
-                    info!(
-                        "evdev: Added keyboard device: {} ({}) with {} keys",
-                        path.display(),
-                        device.name().unwrap_or("Unknown"),
-                        keys.len()
-                    );
-                    devices.insert(path.clone(), device);
-                } else {
-                    debug!(
-                        "evdev: Device {} has KEY events but no keyboard keys",
-                        path.display()
-                    );
-                }
-            } else {
-                debug!(
-                    "evdev: Device {} does not support KEY events",
-                    path.display()
-                );
-            }
-        } else {
+        if let Some(device) = try_open_keyboard(path) {
+            devices.insert(path.clone(), device);
+        }
+    }
+
+    Ok(())
+}
+
+/// Opens `path` and returns it only if the capability heuristic (has KEY
+/// events and at least one typical keyboard key) says it's a keyboard.
+#[cfg(feature = "wayland")]
+fn try_open_keyboard(path: &Path) -> Option<Device> {
+    use evdev::{Device, EventType, KeyCode};
+
+    debug!("evdev: Checking device: {}", path.display());
+    let device = match Device::open(path) {
+        Ok(d) => d,
+        Err(_) => {
             debug!(
                 "evdev: Cannot open device {}, likely permission issue",
                 path.display()
             );
+            return None;
         }
+    };
+
+    if !device.supported_events().contains(EventType::KEY) {
+        debug!("evdev: Device {} does not support KEY events", path.display());
+        return None;
     }
 
-    Ok(())
+    let keys = device
+        .supported_keys()
+        .map(|keys| keys.into_iter().collect::<Vec<_>>())
+        .unwrap_or_default();
+
+    let has_keyboard_keys = keys.iter().any(|&key| {
+        matches!(
+            key,
+            KeyCode::KEY_A
+                | KeyCode::KEY_B
+                | KeyCode::KEY_C
+                | KeyCode::KEY_SPACE
+                | KeyCode::KEY_ENTER
+                | KeyCode::KEY_LEFTSHIFT
+                | KeyCode::KEY_LEFTCTRL
+        )
+    });
+
+    if !has_keyboard_keys {
+        debug!("evdev: Device {} has KEY events but no keyboard keys", path.display());
+        return None;
+    }
+
+    info!(
+        "evdev: Added keyboard device: {} ({}) with {} keys",
+        path.display(),
+        device.name().unwrap_or("Unknown"),
+        keys.len()
+    );
+    Some(device)
 }
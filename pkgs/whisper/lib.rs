@@ -0,0 +1,227 @@
+//! Library surface for the dictation engine. `main.rs` is a thin binary
+//! wrapper around this crate (hotkey listening, CLI parsing, the stdin
+//! fallback); everything reusable by another host — a Flutter/Tauri
+//! frontend, a scripting runtime — lives here instead, including the `ffi`
+//! module for embedding it from outside Rust entirely.
+
+use anyhow::{Context, Result};
+use log::{info, warn};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc as sync_mpsc;
+
+pub mod api;
+pub mod audio;
+pub mod bindings;
+pub mod config;
+pub mod controller;
+pub mod ffi;
+pub mod hotkey;
+pub mod metrics;
+pub mod output;
+pub mod sock;
+pub mod sound;
+pub mod utils;
+#[cfg(feature = "wayland")]
+pub mod xkb;
+
+use config::{Config, OutputType, Service};
+
+/// One unit of output from a `Dictation` engine: a finished transcription,
+/// or an error surfaced to the caller instead of only being logged, since
+/// an embedding host (a GUI, a scripting runtime) may have no stderr to read.
+#[derive(Debug, Clone)]
+pub enum TranscriptEvent {
+    Text { sequence: u64, text: String },
+    Error { sequence: u64, message: String },
+}
+
+/// Dispatches already-resampled samples to whichever service is
+/// configured, writing a scratch audio file (in `config.audio_format`) for
+/// the remote HTTP uploads and skipping that round-trip for the local
+/// whisper.cpp backend. `scratch_path_stem` should have no extension —
+/// `transcribe` appends the one matching `config.audio_format` — and is
+/// removed again before returning, so callers only need to pick a stem
+/// that won't collide with a concurrent transcription. If
+/// `config.compress_cache` is set, the scratch file is deflate-compressed
+/// and retained in the cache directory instead: `utils::cleanup_old_files`/
+/// `enforce_cache_size_budget` own its eventual removal.
+///
+/// Returns a `Transcription` rather than a plain string so callers that
+/// want subtitles or a click-to-seek UI can walk `segments`; callers that
+/// just want the text can call `.text()` instead.
+pub async fn transcribe(
+    config: &Config,
+    service: Service,
+    samples: &[f32],
+    scratch_path_stem: &Path,
+) -> Result<api::Transcription> {
+    if service == Service::Local {
+        let text = api::transcribe_local(config, samples).await?;
+        return Ok(api::Transcription::from_text(text));
+    }
+
+    let scratch_path = scratch_path_stem.with_extension(config.audio_format.file_extension());
+    audio::save_f32_to_wav(
+        &scratch_path,
+        samples,
+        audio::TARGET_SAMPLE_RATE,
+        audio::TARGET_CHANNELS,
+        config.audio_format,
+    )
+    .with_context(|| format!("Failed to save scratch audio file {}", scratch_path.display()))?;
+
+    if config.compress_cache {
+        let compressed_path = utils::compress_cached_file(&scratch_path)
+            .await
+            .with_context(|| format!("Failed to compress cached recording {}", scratch_path.display()))?;
+        let upload_path = utils::decompress_cached_file(&compressed_path)
+            .await
+            .with_context(|| format!("Failed to decompress cached recording {}", compressed_path.display()))?;
+
+        let result = api::provider_for(service).transcribe(config, &upload_path).await;
+
+        if let Err(e) = tokio::fs::remove_file(&upload_path).await {
+            warn!(
+                "Failed to remove decompressed scratch copy {}: {}",
+                upload_path.display(),
+                e
+            );
+        }
+
+        return result;
+    }
+
+    let result = api::provider_for(service).transcribe(config, &scratch_path).await;
+
+    if let Err(e) = tokio::fs::remove_file(&scratch_path).await {
+        warn!(
+            "Failed to remove scratch audio file {}: {}",
+            scratch_path.display(),
+            e
+        );
+    }
+
+    result
+}
+
+/// Thin, stably-named wrapper over `output::process_output` — one of the
+/// primitives an embedding host is expected to call directly rather than
+/// reaching into the `output` module itself.
+pub async fn output_text(config: &Config, output: OutputType, text: &str) -> Result<()> {
+    output::process_output(config, output, text).await
+}
+
+/// Reusable recorder + transcription + output pipeline, decoupled from the
+/// hotkey listener and CLI that drive it in the `main.rs` binary. Built for
+/// embedding: a GUI or scripting host constructs one, calls `start()`/
+/// `stop()` from its own UI events, and drains `TranscriptEvent`s from
+/// `try_recv_event` (or the `ffi` module's C bindings) instead of reading
+/// stdout/stderr.
+pub struct Dictation {
+    config: std::sync::Arc<Config>,
+    recorder: audio::AudioRecorder,
+    cache_dir: PathBuf,
+    runtime: tokio::runtime::Runtime,
+    events_tx: sync_mpsc::Sender<TranscriptEvent>,
+    events_rx: sync_mpsc::Receiver<TranscriptEvent>,
+    sequence: u64,
+}
+
+impl Dictation {
+    pub fn new(config: Config) -> Result<Self> {
+        let cache_dir = utils::get_cache_dir()?.join("audio_recordings");
+        std::fs::create_dir_all(&cache_dir).context("Failed to create cache directory")?;
+        let mut recorder = audio::AudioRecorder::with_device(config.max_time, config.device.clone())
+            .context("Failed to initialize audio recorder")?;
+        recorder.set_vad(config.vad, config.vad_silence_ms, config.vad_threshold_multiplier);
+        // Owns its own runtime so it can spawn the async transcribe/output
+        // pipeline regardless of whether the caller is itself async (the
+        // `main.rs` binary) or plain C with no runtime at all (the `ffi`
+        // bindings).
+        let runtime = tokio::runtime::Runtime::new().context("Failed to start dictation engine runtime")?;
+        let (events_tx, events_rx) = sync_mpsc::channel();
+        Ok(Self {
+            config: std::sync::Arc::new(config),
+            recorder,
+            cache_dir,
+            runtime,
+            events_tx,
+            events_rx,
+            sequence: 0,
+        })
+    }
+
+    pub fn start(&mut self) -> Result<()> {
+        self.recorder.start()
+    }
+
+    /// Stops the recorder and, if any audio was captured, spawns the
+    /// transcribe/output pipeline on the engine's own runtime; the result
+    /// arrives later as a `TranscriptEvent` rather than being awaited here.
+    pub fn stop(&mut self) -> Result<()> {
+        let Some((stream_config, audio_data)) = self.recorder.stop()? else {
+            return Ok(());
+        };
+
+        let sequence = self.sequence;
+        self.sequence += 1;
+        let config = self.config.clone();
+        let cache_dir = self.cache_dir.clone();
+        let events_tx = self.events_tx.clone();
+        self.runtime.spawn(async move {
+            let mut resampled = audio::resample_to(
+                &audio_data,
+                stream_config.sample_rate.0,
+                stream_config.channels,
+                audio::TARGET_SAMPLE_RATE,
+            );
+            if config.vad {
+                let noise_floor =
+                    audio::estimate_noise_floor(&resampled, audio::TARGET_SAMPLE_RATE, audio::TARGET_CHANNELS);
+                resampled = audio::trim_silence(
+                    &resampled,
+                    audio::TARGET_SAMPLE_RATE,
+                    audio::TARGET_CHANNELS,
+                    noise_floor,
+                    config.vad_threshold_multiplier,
+                );
+            }
+            let scratch_path_stem = cache_dir.join(format!("dictation_{}", sequence));
+            let event = match transcribe(&config, config.service, &resampled, &scratch_path_stem).await {
+                Ok(transcription) => {
+                    let text = transcription.text().to_string();
+                    if !text.is_empty() {
+                        if let Err(e) = output_text(&config, config.output, &text).await {
+                            warn!("Dictation engine failed to process output: {}", e);
+                        }
+                    }
+                    TranscriptEvent::Text { sequence, text }
+                }
+                Err(e) => TranscriptEvent::Error { sequence, message: e.to_string() },
+            };
+            let _ = events_tx.send(event);
+        });
+        Ok(())
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.recorder.is_recording()
+    }
+
+    /// Non-blocking poll for the next finished transcription. Returns
+    /// `None` if nothing is ready yet.
+    pub fn try_recv_event(&self) -> Option<TranscriptEvent> {
+        self.events_rx.try_recv().ok()
+    }
+}
+
+impl Drop for Dictation {
+    fn drop(&mut self) {
+        if self.is_recording() {
+            if let Err(e) = self.recorder.stop() {
+                warn!("Error stopping recorder while dropping Dictation: {}", e);
+            }
+        }
+        info!("Dictation engine shut down.");
+    }
+}
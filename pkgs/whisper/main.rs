@@ -6,21 +6,34 @@ use std::process::exit;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
-use tokio::sync::{mpsc, Mutex}; // Use tokio's async Mutex
+use tokio::sync::mpsc;
 
-mod api;
-mod audio;
-mod config;
-mod hotkey;
-mod output;
-mod utils; // For cache dir etc.
+// The engine (recorder, transcription, output, hotkey parsing) lives in the
+// `whisper_dictation` library crate so it can be reused outside this binary
+// (see `lib.rs`/`ffi.rs`); this binary just wires the CLI and hotkey
+// listener up to it.
+use whisper_dictation::config::{Config, OutputType, Service};
+use whisper_dictation::controller::{AudioController, ControllerStatus};
+use whisper_dictation::sound::{self, Cue};
+use whisper_dictation::{api, audio, hotkey, sock, utils};
 
-use config::{Config, OutputType, Service};
-
-// Shared application state
+// Active service/output, swappable at runtime via SwitchService/ChangeOutput
+// hotkey bindings. Touched only from the main loop (a single task), so this
+// is a plain struct rather than behind a `Mutex` — unlike recording state,
+// nothing else needs to read or race on it.
 struct AppState {
-    is_recording: bool,
-    // Potentially other things that need to be shared safely
+    active_service: Service,
+    active_output: OutputType,
+}
+
+/// What the main loop is waiting to hear back about from the audio
+/// controller for the in-flight recording, so a `ControllerStatus::Stopped`/
+/// `SamplesReady` arriving later knows whether to process the audio or
+/// discard it (a cancel) and, if processing, which service/output to use
+/// (the ones active at the moment recording started).
+enum PendingCapture {
+    Capturing { service: Service, output: OutputType },
+    Canceling,
 }
 
 // Global cancellation token
@@ -34,11 +47,31 @@ async fn main() -> Result<()> {
     let config = Config::parse();
     debug!("Parsed Config: {:?}", config);
 
+    if config.list_devices {
+        for device in audio::list_input_devices().context("Failed to list audio input devices")? {
+            let fits = if device.satisfies_limits() { "" } else { " (unsupported: exceeds channel/sample-rate limits)" };
+            println!("{}{}", device.name, fits);
+        }
+        return Ok(());
+    }
+
+    if config.toggle {
+        let socket_path = sock::default_socket_path(&utils::get_cache_dir()?);
+        let response = sock::send_command(&socket_path, "toggle")
+            .await
+            .context("Failed to send toggle command to a running dictation daemon")?;
+        println!("{}", response);
+        return Ok(());
+    }
+
     // --- Validate Configuration ---
     if config.output == OutputType::File && config.file.is_none() {
         anyhow::bail!("Output mode 'file' requires the --file argument.");
     }
-    if config.api_key.is_empty() {
+    if config.service == Service::Local && config.model.is_none() {
+        anyhow::bail!("--service local requires --model <path to a whisper.cpp model file>.");
+    }
+    if config.service != Service::Local && config.api_key.is_empty() {
         anyhow::bail!(
             "API key not provided via --api-key or environment variable ({}).",
             config.service.get_env_var_name()
@@ -54,13 +87,12 @@ async fn main() -> Result<()> {
     let config = Arc::new(config); // Share config immutably
 
     // --- Setup State & Communication Channels ---
-    let app_state = Arc::new(Mutex::new(AppState {
-        is_recording: false,
-    }));
+    let mut app_state = AppState {
+        active_service: config.service,
+        active_output: config.output,
+    };
     // Channel for hotkey events -> main loop
     let (hotkey_tx, mut hotkey_rx) = mpsc::channel::<hotkey::HotkeyEvent>(32);
-    // Channel for main loop -> audio recorder control (optional, could use state)
-    // let (audio_cmd_tx, audio_cmd_rx) = mpsc::channel...
 
     // --- Setup Ctrl+C Handler ---
     ctrlc::set_handler(|| {
@@ -69,12 +101,23 @@ async fn main() -> Result<()> {
     })
     .context("Error setting Ctrl+C handler")?;
 
+    // --- Start Metrics Server (optional, --features metrics) ---
+    #[cfg(feature = "metrics")]
+    if let Some(addr) = config.metrics_addr {
+        std::thread::spawn(move || {
+            if let Err(e) = whisper_dictation::metrics::serve(addr) {
+                error!("Metrics server failed: {}", e);
+            }
+        });
+    }
+
     // --- Start Hotkey Listener ---
     let listener_config = config.clone();
     let listener_tx = hotkey_tx.clone();
+    let (hotkey_ready_tx, hotkey_ready_rx) = tokio::sync::oneshot::channel::<()>();
     tokio::task::spawn_blocking(move || {
         info!("Starting hotkey listener...");
-        if let Err(e) = hotkey::listen_for_hotkeys(listener_config, listener_tx) {
+        if let Err(e) = hotkey::listen_for_hotkeys(listener_config, listener_tx, hotkey_ready_tx) {
             error!("Hotkey listener failed: {}", e);
             // Signal shutdown? Or maybe just proceed without hotkeys?
             IS_RUNNING.store(false, Ordering::SeqCst);
@@ -82,15 +125,64 @@ async fn main() -> Result<()> {
         info!("Hotkey listener thread finished.");
     });
 
-    // --- Initialize Audio Recorder ---
-    let mut recorder = audio::AudioRecorder::new(config.max_time)
+    // --- Initialize Audio Recorder & Controller ---
+    // The recorder itself is handed off to a dedicated thread here and never
+    // touched again from this task; `controller` is the only way the rest of
+    // the program reaches it, and `controller_status_rx` is how it hears
+    // back, so recording state can't race between this loop and anything
+    // else driving the recorder.
+    let mut recorder = audio::AudioRecorder::with_device(config.max_time, config.device.clone())
         .context("Failed to initialize audio recorder")?;
-    let cache_dir = utils::get_cache_dir()?.join("audio_recordings");
+    recorder.set_vad(config.vad, config.vad_silence_ms, config.vad_threshold_multiplier);
+    let (controller, mut controller_status_rx) = AudioController::spawn(recorder);
+
+    let root_cache_dir = utils::get_cache_dir()?;
+    let cache_dir = root_cache_dir.join("audio_recordings");
     tokio::fs::create_dir_all(&cache_dir) // Use tokio's async fs
         .await
         .context("Failed to create cache directory")?;
     info!("Using cache directory: {}", cache_dir.display());
 
+    // --- Wait for Hotkey Listener Readiness ---
+    // Bindings are loaded and the listener thread is about to block on OS
+    // input events by the time it fires this; waiting here (with a timeout,
+    // since a hung listener thread shouldn't wedge startup forever) keeps a
+    // `--toggle` client launched right after the daemon from racing an
+    // uninitialized hotkey listener.
+    match tokio::time::timeout(Duration::from_secs(5), hotkey_ready_rx).await {
+        Ok(Ok(())) => {}
+        Ok(Err(_)) => warn!("Hotkey listener dropped its readiness signal; continuing anyway"),
+        Err(_) => warn!("Timed out waiting for the hotkey listener to become ready; continuing anyway"),
+    }
+
+    // --- Start Control Socket Listener ---
+    // Supplements (doesn't replace) the stdin fallback below, so an external
+    // launcher or window-manager keybinding can drive dictation with
+    // `--toggle` without owning this process's stdin.
+    let is_recording_flag = Arc::new(AtomicBool::new(false));
+    {
+        let socket_path = sock::default_socket_path(&root_cache_dir);
+        let hotkey_tx = hotkey_tx.clone();
+        let is_recording_flag = is_recording_flag.clone();
+        tokio::spawn(async move {
+            if let Err(e) = sock::listen(socket_path, hotkey_tx, is_recording_flag, &IS_RUNNING).await {
+                error!("Control socket listener failed: {}", e);
+            }
+        });
+    }
+
+    let sound_cues = if config.sound {
+        match sound::SoundCues::spawn() {
+            Ok(cues) => Some(cues),
+            Err(e) => {
+                warn!("Failed to initialize sound cues, continuing without them: {}", e);
+                None
+            }
+        }
+    } else {
+        None
+    };
+
     // --- Main Application Loop ---
     info!(
         "Whisper Dictation Ready. Press hotkey ({}:{}) to toggle.",
@@ -136,6 +228,18 @@ async fn main() -> Result<()> {
         info!("Stdin listener task finished.");
     });
 
+    // `recording` mirrors the controller's last-known state so the loop can
+    // decide whether a toggle means start or stop. Unlike before the
+    // controller existed, it's only ever written from `handle_controller_status`
+    // — a `ControllerStatus` is the single source of truth for whether the
+    // recorder is running, since a VAD/`max_time` auto-stop can flip it
+    // without this loop ever sending a command. `pending_capture` remembers
+    // what to do once the controller confirms a stop; the controller thread
+    // processes commands strictly in order, so a second command sent before
+    // the first is acknowledged still can't race the recorder itself.
+    let mut recording = false;
+    let mut pending_capture: Option<PendingCapture> = None;
+
     while IS_RUNNING.load(Ordering::Relaxed) {
         tokio::select! {
             _ = tokio::time::sleep(Duration::from_millis(50)) => {
@@ -143,38 +247,59 @@ async fn main() -> Result<()> {
             Some(event) = hotkey_rx.recv() => {
                 match event {
                     hotkey::HotkeyEvent::ToggleRecording => {
-                        let mut state = app_state.lock().await; // Lock the state
-                        let currently_recording = state.is_recording;
-                         state.is_recording = !currently_recording;
-                         let should_be_recording = state.is_recording;
-                         // Unlock happens automatically when `state` goes out of scope
-
-                        if should_be_recording {
-                            info!(">>> Starting recording... <<<");
-                            recorder.start().unwrap();
+                        if recording {
+                            info!(">>> Stopping recording and processing... <<<");
+                            controller.stop_recording();
                         } else {
-                             info!(">>> Stopping recording and processing... <<<");
-                            match recorder.stop() {
-                                Ok(Some((stream_config, audio_data))) => {
-                                    info!("Recording stopped. Got {} samples.", audio_data.len());
-                                     // Process in background task not to block main loop
-                                     let task_config = config.clone();
-                                     let task_cache_dir = cache_dir.clone();
-                                     tokio::spawn(async move {
-                                         process_recorded_audio(task_config, task_cache_dir, stream_config, audio_data).await;
-                                     });
-                                }
-                                Ok(None) => {
-                                     warn!("Recording stopped but no audio data captured.");
-                                }
-                                Err(e) => {
-                                     error!("Error stopping recording: {}", e);
-                                }
-                             }
-                         }
-                     }
-                 }
-             }
+                            info!(">>> Starting recording... <<<");
+                            pending_capture = Some(PendingCapture::Capturing {
+                                service: app_state.active_service,
+                                output: app_state.active_output,
+                            });
+                            start_capture(&controller, &config, app_state.active_service, app_state.active_output, &sound_cues);
+                        }
+                    }
+                    hotkey::HotkeyEvent::PushToTalkStart => {
+                        if !recording {
+                            info!(">>> Push-to-talk: starting recording... <<<");
+                            pending_capture = Some(PendingCapture::Capturing {
+                                service: app_state.active_service,
+                                output: app_state.active_output,
+                            });
+                            start_capture(&controller, &config, app_state.active_service, app_state.active_output, &sound_cues);
+                        }
+                    }
+                    hotkey::HotkeyEvent::PushToTalkStop => {
+                        if recording {
+                            info!(">>> Push-to-talk: stopping recording and processing... <<<");
+                            controller.stop_recording();
+                        }
+                    }
+                    hotkey::HotkeyEvent::CancelRecording => {
+                        if recording {
+                            info!(">>> Recording canceled, discarding captured audio. <<<");
+                            pending_capture = Some(PendingCapture::Canceling);
+                            controller.stop_recording();
+                        }
+                    }
+                    hotkey::HotkeyEvent::SwitchService(service) => {
+                        app_state.active_service = service;
+                        info!("Switched active service to {:?}", service);
+                    }
+                    hotkey::HotkeyEvent::ChangeOutput(output) => {
+                        app_state.active_output = output;
+                        info!("Switched active output mode to {:?}", output);
+                    }
+                    hotkey::HotkeyEvent::SwitchMode(_) => {
+                        // Consumed by the listener to switch its own binding
+                        // table; it should never reach the main loop.
+                        debug!("Ignoring stray SwitchMode event in main loop.");
+                    }
+                }
+            }
+            Some(status) = controller_status_rx.recv() => {
+                handle_controller_status(status, &mut recording, &is_recording_flag, &mut pending_capture, &config, &cache_dir, &sound_cues);
+            }
         }
     } // End main loop
 
@@ -189,71 +314,325 @@ async fn main() -> Result<()> {
         Err(e) => warn!("Error during temporary file cleanup: {}", e),
     }
 
+    // The control socket listener also removes this on its own exit (it
+    // polls `IS_RUNNING`), but that's a race with process exit; clean it up
+    // here too so a `--toggle` client never finds a stale socket file.
+    let socket_path = sock::default_socket_path(&root_cache_dir);
+    if socket_path.exists() {
+        if let Err(e) = std::fs::remove_file(&socket_path) {
+            warn!("Failed to remove control socket {}: {}", socket_path.display(), e);
+        }
+    }
+
+    if let Some(max_cache_bytes) = config.max_cache_bytes {
+        match utils::enforce_cache_size_budget(&cache_dir, max_cache_bytes).await {
+            Ok(0) => {}
+            Ok(count) => info!(
+                "Evicted {} cached recordings to stay under the {}-byte cache budget.",
+                count, max_cache_bytes
+            ),
+            Err(e) => warn!("Error enforcing cache size budget: {}", e),
+        }
+    }
+
+    #[cfg(feature = "metrics")]
+    if let Some(gateway_url) = &config.metrics_pushgateway {
+        match whisper_dictation::metrics::push(gateway_url) {
+            Ok(()) => info!("Pushed metrics to Pushgateway at {}", gateway_url),
+            Err(e) => warn!("Failed to push metrics to Pushgateway: {}", e),
+        }
+    }
+
     info!("Whisper Dictation finished.");
     exit(0);
 }
 
+// Starts capture in whichever mode is configured: `--stream` feeds
+// overlapping windows to the transcription pipeline as they're ready, while
+// the default mode just asks the controller to open the stream and waits
+// for a later `ControllerStatus::SamplesReady`/`Stopped`. Either way,
+// confirmation (and the `RecordingStarted` cue) arrives asynchronously via
+// `handle_controller_status` rather than from this call directly.
+fn start_capture(
+    controller: &AudioController,
+    config: &Arc<Config>,
+    service: Service,
+    output: OutputType,
+    sound_cues: &Option<sound::SoundCues>,
+) {
+    if !config.stream {
+        controller.start_recording();
+        return;
+    }
+
+    let (chunk_tx, chunk_rx) = crossbeam_channel::unbounded::<audio::StreamChunk>();
+    controller.start_streaming(chunk_tx);
+
+    // Bridge the sync crossbeam receiver (fed from the ring-buffer consumer
+    // thread) into the async world, the same way the rdev hotkey listener
+    // bridges into its tokio channel. If the controller failed to start
+    // streaming, `chunk_tx` is dropped without ever being handed to a
+    // capture thread, so `chunk_rx.recv()` below returns `Err` right away
+    // and this bridge (and the processor task it feeds) shuts down cleanly.
+    let (async_tx, mut async_rx) = tokio::sync::mpsc::unbounded_channel::<audio::StreamChunk>();
+    std::thread::spawn(move || {
+        while let Ok(chunk) = chunk_rx.recv() {
+            if async_tx.send(chunk).is_err() {
+                break;
+            }
+        }
+    });
+
+    let task_config = config.clone();
+    let task_sound_cues = sound_cues.clone();
+    tokio::spawn(async move {
+        while let Some(chunk) = async_rx.recv().await {
+            process_stream_chunk(task_config.clone(), chunk, service, output, task_sound_cues.clone()).await;
+        }
+        info!("Streaming chunk processor finished.");
+    });
+}
+
+// Reacts to one status update from the audio controller: keeps `recording`/
+// `is_recording_flag` in sync (the only place either is written — a status
+// here may be reporting an auto-stop the main loop never asked for, e.g.
+// VAD silence or hitting `--max-time`, just as easily as a hotkey-triggered
+// one), plays the matching sound cue, and for a completed non-streaming
+// capture either discards the samples (a cancel) or spawns the
+// save/transcribe/output pipeline for them.
+fn handle_controller_status(
+    status: ControllerStatus,
+    recording: &mut bool,
+    is_recording_flag: &Arc<AtomicBool>,
+    pending_capture: &mut Option<PendingCapture>,
+    config: &Arc<Config>,
+    cache_dir: &PathBuf,
+    sound_cues: &Option<sound::SoundCues>,
+) {
+    match status {
+        ControllerStatus::Recording => {
+            *recording = true;
+            is_recording_flag.store(true, Ordering::Relaxed);
+            if let Some(cues) = sound_cues {
+                cues.play(Cue::RecordingStarted);
+            }
+        }
+        ControllerStatus::Stopped => {
+            *recording = false;
+            is_recording_flag.store(false, Ordering::Relaxed);
+            match pending_capture.take() {
+                Some(PendingCapture::Canceling) => debug!("Recording canceled."),
+                Some(PendingCapture::Capturing { .. }) if config.stream => {
+                    // `--stream` mode already transcribed/output each window
+                    // as it arrived, so an empty `stop` result here just
+                    // means the stream halted cleanly.
+                    if let Some(cues) = sound_cues {
+                        cues.play(Cue::RecordingStopped);
+                    }
+                }
+                Some(PendingCapture::Capturing { .. }) => {
+                    // Non-stream mode with zero frames captured: nothing to
+                    // transcribe, so warn (matching the pre-controller
+                    // `stop_and_process`'s `Ok(None)` handling) instead of
+                    // playing the stop-succeeded cue.
+                    warn!("Recording stopped but no audio data captured.");
+                }
+                None => warn!("Recording stopped with no pending capture; ignoring."),
+            }
+        }
+        ControllerStatus::SamplesReady { stream_config, samples } => {
+            *recording = false;
+            is_recording_flag.store(false, Ordering::Relaxed);
+            match pending_capture.take() {
+                Some(PendingCapture::Canceling) => {
+                    debug!("Discarding {} samples from a canceled recording.", samples.len());
+                }
+                Some(PendingCapture::Capturing { service, output }) => {
+                    info!("Recording stopped. Got {} samples.", samples.len());
+                    if let Some(cues) = sound_cues {
+                        cues.play(Cue::RecordingStopped);
+                    }
+                    let task_config = config.clone();
+                    let task_cache_dir = cache_dir.clone();
+                    let task_sound_cues = sound_cues.clone();
+                    tokio::spawn(async move {
+                        process_recorded_audio(
+                            task_config,
+                            task_cache_dir,
+                            stream_config,
+                            samples,
+                            service,
+                            output,
+                            task_sound_cues,
+                        )
+                        .await;
+                    });
+                }
+                None => warn!("Got recorded samples with no pending capture; discarding."),
+            }
+        }
+        ControllerStatus::Error(e) => {
+            *recording = false;
+            is_recording_flag.store(false, Ordering::Relaxed);
+            error!("Audio controller error: {}", e);
+            *pending_capture = None;
+        }
+    }
+}
+
+// Decodes a chunk's in-memory WAV bytes (16-bit PCM at
+// audio::TARGET_SAMPLE_RATE/TARGET_CHANNELS) back to f32 samples, for the
+// local service which transcribes from samples rather than a WAV upload.
+fn decode_wav_samples(wav_bytes: &[u8]) -> Result<Vec<f32>> {
+    let mut reader =
+        hound::WavReader::new(std::io::Cursor::new(wav_bytes)).context("Failed to parse stream chunk WAV data")?;
+    reader
+        .samples::<i16>()
+        .map(|s| s.map(|v| v as f32 / 32768.0).context("Failed to read WAV sample"))
+        .collect()
+}
+
+// Transcribes one streamed window and appends its text to the chosen
+// output, independent of the other windows in flight.
+async fn process_stream_chunk(
+    config: Arc<Config>,
+    chunk: audio::StreamChunk,
+    service: Service,
+    output: OutputType,
+    sound_cues: Option<sound::SoundCues>,
+) {
+    // Local transcription reads the WAV bytes already in memory; the
+    // remote services need an actual file path to upload.
+    let transcription_result = if service == Service::Local {
+        match decode_wav_samples(&chunk.wav_bytes) {
+            Ok(samples) => api::transcribe_local(&config, &samples).await,
+            Err(e) => Err(e),
+        }
+    } else {
+        let tmp_path = std::env::temp_dir().join(format!("dictation_stream_{}.wav", chunk.sequence));
+        if let Err(e) = tokio::fs::write(&tmp_path, &chunk.wav_bytes).await {
+            error!("Failed to write stream chunk #{} to temp file: {}", chunk.sequence, e);
+            return;
+        }
+        let result = api::provider_for(service)
+            .transcribe(&config, &tmp_path)
+            .await
+            .map(|transcription| transcription.text().to_string());
+        if let Err(e) = tokio::fs::remove_file(&tmp_path).await {
+            warn!("Failed to remove stream chunk temp file {}: {}", tmp_path.display(), e);
+        }
+        result
+    };
+
+    match transcription_result {
+        Ok(text) if !text.is_empty() => {
+            info!("Stream chunk #{} transcribed: {}...", chunk.sequence, text.chars().take(50).collect::<String>());
+            match whisper_dictation::output_text(&config, output, &text).await {
+                Ok(()) => {
+                    if let Some(cues) = &sound_cues {
+                        cues.play(Cue::TranscriptionDone);
+                    }
+                    if config.notify {
+                        whisper_dictation::output::notify_success(&text).await;
+                    }
+                }
+                Err(e) => {
+                    error!("Failed to process stream chunk #{} output: {}", chunk.sequence, e);
+                    if let Some(cues) = &sound_cues {
+                        cues.play(Cue::TranscriptionFailed);
+                    }
+                    if config.notify {
+                        whisper_dictation::output::notify_failure(&e.to_string()).await;
+                    }
+                }
+            }
+        }
+        Ok(_) => debug!("Stream chunk #{} transcribed to empty text.", chunk.sequence),
+        Err(e) => {
+            error!("Stream chunk #{} transcription failed: {}", chunk.sequence, e);
+            if let Some(cues) = &sound_cues {
+                cues.play(Cue::TranscriptionFailed);
+            }
+            if config.notify {
+                whisper_dictation::output::notify_failure(&e.to_string()).await;
+            }
+        }
+    }
+}
+
 // Function to handle processing audio data (can be spawned as a task)
 async fn process_recorded_audio(
     config: Arc<Config>,
     cache_dir: PathBuf,
     stream_config: StreamConfig,
     audio_data: Vec<f32>,
+    service: Service,
+    output: OutputType,
+    sound_cues: Option<sound::SoundCues>,
 ) {
-    // 1. Save to WAV
-    let filename = format!("recording_{}.wav", chrono::Utc::now().timestamp_millis());
-    let wav_path = cache_dir.join(&filename);
-    info!("Saving audio to: {}", wav_path.display());
-
-    match audio::save_f32_to_wav(
-        &wav_path,
+    // 1. Resample to what STT backends expect, trim silence if enabled
+    let mut resampled = audio::resample_to(
         &audio_data,
         stream_config.sample_rate.0,
         stream_config.channels,
-    ) {
-        Ok(_) => {
-            info!("Audio saved successfully.");
-            // 2. Transcribe using API
-            let transcription_result = match config.service {
-                Service::OpenAI => api::transcribe_openai(&config, &wav_path).await,
-                Service::Replicate => api::transcribe_replicate(&config, &wav_path).await,
-                Service::ElevenLabs => {
-                    api::transcribe_elevenlabs(&config, &wav_path.as_path()).await
-                }
-            };
-
-            match transcription_result {
-                Ok(text) => {
-                    if !text.is_empty() {
-                        info!(
-                            "Transcription successful: {}...",
-                            text.chars().take(50).collect::<String>()
-                        );
-                        // 3. Process Output
-                        if let Err(e) = output::process_output(&config, &text.as_str()).await {
-                            error!("Failed to process output: {}", e);
-                        }
-                    } else {
-                        warn!("API returned an empty transcription.");
+        audio::TARGET_SAMPLE_RATE,
+    );
+    if config.vad {
+        let noise_floor =
+            audio::estimate_noise_floor(&resampled, audio::TARGET_SAMPLE_RATE, audio::TARGET_CHANNELS);
+        resampled = audio::trim_silence(
+            &resampled,
+            audio::TARGET_SAMPLE_RATE,
+            audio::TARGET_CHANNELS,
+            noise_floor,
+            config.vad_threshold_multiplier,
+        );
+    }
+
+    // 2. Transcribe (the library handles the scratch file round-trip for
+    // remote services and skips it for the local whisper.cpp backend) and
+    // 3. process output.
+    let scratch_path_stem = cache_dir.join(format!("recording_{}", chrono::Utc::now().timestamp_millis()));
+    match whisper_dictation::transcribe(&config, service, &resampled, &scratch_path_stem).await {
+        Ok(transcription) if !transcription.text().is_empty() => {
+            info!(
+                "Transcription successful: {}...",
+                transcription.text().chars().take(50).collect::<String>()
+            );
+            match whisper_dictation::output_text(&config, output, transcription.text()).await {
+                Ok(()) => {
+                    if let Some(cues) = &sound_cues {
+                        cues.play(Cue::TranscriptionDone);
+                    }
+                    if config.notify {
+                        whisper_dictation::output::notify_success(transcription.text()).await;
                     }
                 }
                 Err(e) => {
-                    error!("API transcription failed: {}", e);
+                    error!("Failed to process output: {}", e);
+                    if let Some(cues) = &sound_cues {
+                        cues.play(Cue::TranscriptionFailed);
+                    }
+                    if config.notify {
+                        whisper_dictation::output::notify_failure(&e.to_string()).await;
+                    }
                 }
             }
-
-            // 4. Clean up temporary WAV file
-            match tokio::fs::remove_file(&wav_path).await {
-                Ok(_) => debug!("Removed temporary file: {}", wav_path.display()),
-                Err(e) => warn!(
-                    "Failed to remove temporary file {}: {}",
-                    wav_path.display(),
-                    e
-                ),
+        }
+        Ok(_) => {
+            warn!("Transcription returned empty text.");
+            if config.notify {
+                whisper_dictation::output::notify_failure("Transcription returned empty text.").await;
             }
         }
         Err(e) => {
-            error!("Failed to save WAV file {}: {}", wav_path.display(), e);
+            error!("Transcription failed: {}", e);
+            if let Some(cues) = &sound_cues {
+                cues.play(Cue::TranscriptionFailed);
+            }
+            if config.notify {
+                whisper_dictation::output::notify_failure(&e.to_string()).await;
+            }
         }
     }
 }
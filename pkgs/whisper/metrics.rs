@@ -0,0 +1,136 @@
+//! Optional Prometheus instrumentation for the transcription paths, enabled
+//! by the `metrics` feature. Every `record_*` function here is a no-op when
+//! the feature is off, so `api.rs`'s shared retry loop and the Replicate
+//! poller can call them unconditionally instead of scattering `#[cfg]`
+//! through the request logic itself. `serve` and `push` exist only with the
+//! feature on.
+
+#[cfg(feature = "metrics")]
+mod imp {
+    use anyhow::{Context, Result};
+    use once_cell::sync::Lazy;
+    use prometheus::{register_counter_vec, register_histogram, register_histogram_vec};
+    use prometheus::{CounterVec, Encoder, Histogram, HistogramVec, TextEncoder};
+    use std::collections::HashMap;
+    use std::net::SocketAddr;
+
+    static REQUESTS_TOTAL: Lazy<CounterVec> = Lazy::new(|| {
+        register_counter_vec!(
+            "dictation_transcription_requests_total",
+            "Transcription attempts started, by provider.",
+            &["provider"]
+        )
+        .expect("metric registration should not fail")
+    });
+
+    static SUCCESSES_TOTAL: Lazy<CounterVec> = Lazy::new(|| {
+        register_counter_vec!(
+            "dictation_transcription_successes_total",
+            "Transcription attempts that eventually succeeded, by provider.",
+            &["provider"]
+        )
+        .expect("metric registration should not fail")
+    });
+
+    static FAILURES_TOTAL: Lazy<CounterVec> = Lazy::new(|| {
+        register_counter_vec!(
+            "dictation_transcription_failures_total",
+            "Transcription attempts that ended in a fatal error or exhausted their retries, by provider.",
+            &["provider"]
+        )
+        .expect("metric registration should not fail")
+    });
+
+    static RETRIES_TOTAL: Lazy<CounterVec> = Lazy::new(|| {
+        register_counter_vec!(
+            "dictation_transcription_retries_total",
+            "Retryable failures (429/5xx/timeout/connect) observed before success or exhaustion, by provider.",
+            &["provider"]
+        )
+        .expect("metric registration should not fail")
+    });
+
+    static LATENCY_SECONDS: Lazy<HistogramVec> = Lazy::new(|| {
+        register_histogram_vec!(
+            "dictation_transcription_latency_seconds",
+            "End-to-end transcription request latency, by provider.",
+            &["provider"]
+        )
+        .expect("metric registration should not fail")
+    });
+
+    static REPLICATE_POLL_SECONDS: Lazy<Histogram> = Lazy::new(|| {
+        register_histogram!(
+            "dictation_replicate_poll_seconds",
+            "Time from a Replicate prediction being created to it reaching a terminal status."
+        )
+        .expect("metric registration should not fail")
+    });
+
+    pub fn record_request(provider: &str) {
+        REQUESTS_TOTAL.with_label_values(&[provider]).inc();
+    }
+
+    pub fn record_success(provider: &str) {
+        SUCCESSES_TOTAL.with_label_values(&[provider]).inc();
+    }
+
+    pub fn record_failure(provider: &str) {
+        FAILURES_TOTAL.with_label_values(&[provider]).inc();
+    }
+
+    pub fn record_retry(provider: &str) {
+        RETRIES_TOTAL.with_label_values(&[provider]).inc();
+    }
+
+    pub fn record_latency(provider: &str, seconds: f64) {
+        LATENCY_SECONDS.with_label_values(&[provider]).observe(seconds);
+    }
+
+    pub fn record_replicate_poll_seconds(seconds: f64) {
+        REPLICATE_POLL_SECONDS.observe(seconds);
+    }
+
+    /// Serves the default registry's metrics as `GET /metrics` on `addr`,
+    /// blocking the calling thread forever. Callers spawn this on its own
+    /// thread, the same way `main.rs` spawns the hotkey listener.
+    pub fn serve(addr: SocketAddr) -> Result<()> {
+        let server = tiny_http::Server::http(addr)
+            .map_err(|e| anyhow::anyhow!("Failed to bind metrics server on {}: {}", addr, e))?;
+        log::info!("Serving Prometheus metrics on http://{}/metrics", addr);
+        for request in server.incoming_requests() {
+            let encoder = TextEncoder::new();
+            let mut buffer = Vec::new();
+            if let Err(e) = encoder.encode(&prometheus::gather(), &mut buffer) {
+                log::warn!("Failed to encode metrics: {}", e);
+                buffer.clear();
+            }
+            let header = tiny_http::Header::from_bytes(&b"Content-Type"[..], encoder.format_type().as_bytes())
+                .expect("static header name/value should be valid");
+            if let Err(e) = request.respond(tiny_http::Response::from_data(buffer).with_header(header)) {
+                log::warn!("Failed to write metrics response: {}", e);
+            }
+        }
+        Ok(())
+    }
+
+    /// Pushes the accumulated metrics to a Prometheus Pushgateway once, for
+    /// a short-lived CLI dictation run that a scrape would otherwise miss
+    /// entirely. Meant to be called right before the process exits.
+    pub fn push(gateway_url: &str) -> Result<()> {
+        prometheus::push_metrics("whisper_dictation", HashMap::new(), gateway_url, prometheus::gather(), None)
+            .context("Failed to push metrics to Pushgateway")
+    }
+}
+
+#[cfg(not(feature = "metrics"))]
+mod imp {
+    pub fn record_request(_provider: &str) {}
+    pub fn record_success(_provider: &str) {}
+    pub fn record_failure(_provider: &str) {}
+    pub fn record_retry(_provider: &str) {}
+    pub fn record_latency(_provider: &str, _seconds: f64) {}
+    pub fn record_replicate_poll_seconds(_seconds: f64) {}
+}
+
+pub use imp::*;
@@ -3,13 +3,14 @@ use anyhow::{anyhow, Context, Result};
 use arboard::Clipboard;
 use enigo::{Enigo, Key, Keyboard, Settings};
 use log::{debug, info, warn};
+use notify_rust::Notification;
 use std::path::Path;
 use std::thread;
 use std::time::Duration;
 use tokio::fs::OpenOptions;
 use tokio::io::AsyncWriteExt; // for append
 
-pub async fn process_output(config: &Config, text: &str) -> Result<()> {
+pub async fn process_output(config: &Config, output: OutputType, text: &str) -> Result<()> {
     if text.is_empty() {
         warn!("Received empty transcription. Skipping output.");
         return Ok(());
@@ -17,7 +18,7 @@ pub async fn process_output(config: &Config, text: &str) -> Result<()> {
 
     let output_text = text.trim(); // Ensure no leading/trailing whitespace
 
-    match config.output {
+    match output {
         OutputType::Clipboard => {
             write_to_clipboard(output_text)
         }
@@ -39,6 +40,53 @@ pub async fn process_output(config: &Config, text: &str) -> Result<()> {
     }
 }
 
+// --- Desktop Notifications ---
+//
+// Independent of `OutputType`: the tool typically runs in the background
+// with no visible terminal, so a failed or empty transcription is
+// otherwise silent. Gated behind `--notify` rather than its own
+// `OutputType` variant, since a user picking `--output paste` still wants
+// their real output *and* a toast when it fails.
+
+/// Shows a toast with the first ~100 chars of a successful transcript.
+/// `Notification::show()` is a blocking D-Bus round-trip, so it runs on a
+/// blocking thread rather than stalling the tokio worker this is called
+/// from (matching `utils::compress_cached_file`'s pattern).
+pub async fn notify_success(text: &str) {
+    let preview: String = text.chars().take(100).collect();
+    let result = tokio::task::spawn_blocking(move || {
+        Notification::new()
+            .summary("Dictation transcribed")
+            .body(&preview)
+            .show()
+    })
+    .await;
+    match result {
+        Ok(Err(e)) => warn!("Failed to show transcription notification: {}", e),
+        Err(e) => warn!("Notification task panicked: {}", e),
+        Ok(Ok(())) => {}
+    }
+}
+
+/// Shows a warning toast with the failure reason (empty transcription, API
+/// error, output delivery error, ...). See `notify_success` for why this
+/// runs on a blocking thread.
+pub async fn notify_failure(reason: &str) {
+    let reason = reason.to_string();
+    let result = tokio::task::spawn_blocking(move || {
+        Notification::new()
+            .summary("Dictation failed")
+            .body(&reason)
+            .show()
+    })
+    .await;
+    match result {
+        Ok(Err(e)) => warn!("Failed to show failure notification: {}", e),
+        Err(e) => warn!("Notification task panicked: {}", e),
+        Ok(Ok(())) => {}
+    }
+}
+
 fn write_to_clipboard(text: &str) -> Result<()> {
     let mut clipboard = Clipboard::new().context("Failed to initialize clipboard")?;
     clipboard.set_text(text.to_string())?; // arboard requires String
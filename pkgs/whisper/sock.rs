@@ -0,0 +1,144 @@
+//! Unix-domain-socket control channel so an external launcher or
+//! window-manager keybinding can drive dictation (`toggle`/`start`/`stop`/
+//! `status`/`quit`) without owning this process's stdin, the way the
+//! existing stdin-readline fallback in `main.rs` requires. Commands are
+//! forwarded into the same `hotkey_tx` channel a real hotkey press uses, so
+//! the main loop can't tell the difference.
+//!
+//! Windows named-pipe support isn't implemented here (`UnixListener` has no
+//! Windows equivalent); Windows users should keep using the stdin fallback
+//! or a hotkey binding until that's added.
+
+use crate::hotkey::HotkeyEvent;
+use anyhow::{Context, Result};
+use log::{debug, error, info, warn};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{UnixListener, UnixStream};
+use tokio::sync::mpsc;
+
+/// Well-known socket path under the cache directory, so a `--toggle` client
+/// can find a running daemon with no extra configuration.
+pub fn default_socket_path(cache_dir: &Path) -> PathBuf {
+    cache_dir.join("dictation.sock")
+}
+
+/// Accepts control connections on `path` until `is_running` flips to
+/// `false`, forwarding parsed commands into `hotkey_tx`. `is_recording` is
+/// kept current by the caller so `status` queries don't need a round-trip
+/// into the main loop. Removes the socket file on the way out.
+pub async fn listen(
+    path: PathBuf,
+    hotkey_tx: mpsc::Sender<HotkeyEvent>,
+    is_recording: Arc<AtomicBool>,
+    is_running: &'static AtomicBool,
+) -> Result<()> {
+    if path.exists() {
+        std::fs::remove_file(&path)
+            .with_context(|| format!("Failed to remove stale control socket {}", path.display()))?;
+    }
+    let listener = UnixListener::bind(&path)
+        .with_context(|| format!("Failed to bind control socket at {}", path.display()))?;
+    info!("Listening for control commands on {}", path.display());
+
+    loop {
+        tokio::select! {
+            // Polls shutdown every 200 ms rather than adding a separate
+            // shutdown channel.
+            _ = tokio::time::sleep(Duration::from_millis(200)) => {
+                if !is_running.load(Ordering::Relaxed) {
+                    break;
+                }
+            }
+            accept_result = listener.accept() => {
+                let (stream, _addr) = match accept_result {
+                    Ok(pair) => pair,
+                    Err(e) => {
+                        warn!("Failed to accept control connection: {}", e);
+                        continue;
+                    }
+                };
+                let hotkey_tx = hotkey_tx.clone();
+                let is_recording = is_recording.clone();
+                tokio::spawn(async move {
+                    if let Err(e) = handle_connection(stream, hotkey_tx, is_recording, is_running).await {
+                        warn!("Control connection error: {}", e);
+                    }
+                });
+            }
+        }
+    }
+
+    let _ = std::fs::remove_file(&path);
+    Ok(())
+}
+
+async fn handle_connection(
+    stream: UnixStream,
+    hotkey_tx: mpsc::Sender<HotkeyEvent>,
+    is_recording: Arc<AtomicBool>,
+    is_running: &'static AtomicBool,
+) -> Result<()> {
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = BufReader::new(reader).lines();
+    while let Some(line) = lines.next_line().await? {
+        let command = line.trim();
+        debug!("Control socket received command: {}", command);
+        let response = match command {
+            "toggle" => {
+                let _ = hotkey_tx.send(HotkeyEvent::ToggleRecording).await;
+                "ok"
+            }
+            "start" => {
+                let _ = hotkey_tx.send(HotkeyEvent::PushToTalkStart).await;
+                "ok"
+            }
+            "stop" => {
+                let _ = hotkey_tx.send(HotkeyEvent::PushToTalkStop).await;
+                "ok"
+            }
+            "status" => {
+                if is_recording.load(Ordering::Relaxed) {
+                    "recording"
+                } else {
+                    "idle"
+                }
+            }
+            "quit" => {
+                is_running.store(false, Ordering::SeqCst);
+                "ok"
+            }
+            "" => continue,
+            other => {
+                error!("Unknown control command: {}", other);
+                "error: unknown command"
+            }
+        };
+        writer.write_all(response.as_bytes()).await?;
+        writer.write_all(b"\n").await?;
+    }
+    Ok(())
+}
+
+/// One-shot client used by the `--toggle` CLI flag: connects to a running
+/// daemon's control socket, sends a single command, and returns its
+/// response line.
+pub async fn send_command(path: &Path, command: &str) -> Result<String> {
+    let stream = UnixStream::connect(path).await.with_context(|| {
+        format!(
+            "Failed to connect to control socket {} (is dictation running?)",
+            path.display()
+        )
+    })?;
+    let (reader, mut writer) = stream.into_split();
+    writer.write_all(command.as_bytes()).await?;
+    writer.write_all(b"\n").await?;
+    BufReader::new(reader)
+        .lines()
+        .next_line()
+        .await?
+        .context("Control socket closed before responding")
+}
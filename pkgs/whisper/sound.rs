@@ -0,0 +1,113 @@
+//! Audio feedback cues (start/stop/done/error tones) so a hands-free user
+//! gets non-visual confirmation that recording toggled or transcription
+//! landed, gated behind `--sound`. Tones are synthesized sine sweeps rather
+//! than bundled sound assets, since this crate has no asset pipeline.
+//!
+//! `rodio`'s `OutputStream` isn't reliably `Send` on every platform, so it's
+//! kept on a small dedicated thread for the process lifetime; callers just
+//! send a `Cue` over a plain `mpsc::Sender`, which is cheap to clone into
+//! any async task that needs to play one.
+
+use anyhow::{anyhow, Context, Result};
+use log::warn;
+use rodio::source::{SineWave, Source};
+use rodio::{OutputStream, OutputStreamHandle, Sink};
+use std::sync::mpsc;
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy)]
+pub enum Cue {
+    /// Rising tone confirming `recorder.start()` succeeded.
+    RecordingStarted,
+    /// Falling tone confirming recording stopped.
+    RecordingStopped,
+    /// Confirmation chime after output lands successfully.
+    TranscriptionDone,
+    /// Error buzz when transcription or output fails.
+    TranscriptionFailed,
+}
+
+#[derive(Clone)]
+pub struct SoundCues {
+    tx: mpsc::Sender<Cue>,
+}
+
+impl SoundCues {
+    /// Spawns the dedicated playback thread and blocks until its audio
+    /// output stream is open (or failed to open), so callers get a real
+    /// error instead of silently losing every cue.
+    pub fn spawn() -> Result<Self> {
+        let (tx, rx) = mpsc::channel::<Cue>();
+        let (ready_tx, ready_rx) = mpsc::channel::<Result<(), String>>();
+
+        std::thread::Builder::new()
+            .name("sound-cues".to_string())
+            .spawn(move || {
+                let (_stream, stream_handle) = match OutputStream::try_default() {
+                    Ok(pair) => pair,
+                    Err(e) => {
+                        let _ = ready_tx.send(Err(e.to_string()));
+                        return;
+                    }
+                };
+                let _ = ready_tx.send(Ok(()));
+                while let Ok(cue) = rx.recv() {
+                    play_cue(&stream_handle, cue);
+                }
+            })
+            .context("Failed to spawn the sound cue thread")?;
+
+        ready_rx
+            .recv()
+            .context("Sound cue thread exited before it finished starting")?
+            .map_err(|e| anyhow!("Failed to open an audio output stream for sound cues: {}", e))?;
+
+        Ok(Self { tx })
+    }
+
+    /// Queues a cue for playback. Never blocks the caller; if the playback
+    /// thread is gone this just logs and drops it.
+    pub fn play(&self, cue: Cue) {
+        if self.tx.send(cue).is_err() {
+            warn!("Sound cue thread is gone; dropping {:?} cue", cue);
+        }
+    }
+}
+
+fn play_cue(stream_handle: &OutputStreamHandle, cue: Cue) {
+    let sink = match Sink::try_new(stream_handle) {
+        Ok(sink) => sink,
+        Err(e) => {
+            warn!("Failed to start a sound cue: {}", e);
+            return;
+        }
+    };
+
+    match cue {
+        Cue::RecordingStarted => append_sweep(&sink, 440.0, 880.0, Duration::from_millis(150)),
+        Cue::RecordingStopped => append_sweep(&sink, 880.0, 440.0, Duration::from_millis(150)),
+        Cue::TranscriptionDone => append_sweep(&sink, 660.0, 990.0, Duration::from_millis(120)),
+        Cue::TranscriptionFailed => sink.append(
+            SineWave::new(180.0)
+                .take_duration(Duration::from_millis(250))
+                .amplify(0.25),
+        ),
+    }
+
+    // Detach rather than block on `sink.sleep_until_end()`: the caller (the
+    // main loop or a spawned transcription task) must not stall waiting for
+    // a 150ms beep to finish.
+    sink.detach();
+}
+
+/// Queues a short linear frequency sweep, which reads as "rising"/"falling"
+/// rather than a flat beep.
+fn append_sweep(sink: &Sink, freq_start: f32, freq_end: f32, duration: Duration) {
+    const STEPS: u32 = 8;
+    let step_duration = duration / STEPS;
+    for i in 0..STEPS {
+        let t = i as f32 / (STEPS - 1) as f32;
+        let freq = freq_start + (freq_end - freq_start) * t;
+        sink.append(SineWave::new(freq).take_duration(step_duration).amplify(0.2));
+    }
+}
@@ -1,13 +1,17 @@
 use anyhow::{Context, Result};
 use directories::ProjectDirs;
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use flate2::Compression;
 use log::debug;
-use std::path::PathBuf;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
 use std::time::{Duration, SystemTime};
 use tokio::fs; // Use tokio's async fs
 
 // Get cross-platform cache directory
 pub fn get_cache_dir() -> Result<PathBuf> {
-    let proj_dirs = ProjectDirs::from("com", "YourAppNameOrOrg", "WhisperDictationRust") // Adjust qualifier, org, app name
+    let proj_dirs = ProjectDirs::from("", "", env!("CARGO_PKG_NAME"))
         .context("Failed to determine application directories")?;
     Ok(proj_dirs.cache_dir().to_path_buf())
 }
@@ -24,7 +28,7 @@ pub async fn cleanup_old_files(dir: &PathBuf, max_age: Duration) -> Result<usize
 
     while let Some(entry) = entries.next_entry().await? {
         let path = entry.path();
-        if path.is_file() && path.extension().map_or(false, |ext| ext == "wav") { // Simple check for .wav
+        if path.is_file() && is_cached_recording(&path) {
              let metadata = fs::metadata(&path).await?;
              if let Ok(modified) = metadata.modified() {
                  if modified < cutoff {
@@ -40,3 +44,186 @@ pub async fn cleanup_old_files(dir: &PathBuf, max_age: Duration) -> Result<usize
     }
     Ok(count)
 }
+
+/// Matches the file kinds `cleanup_old_files`/`enforce_cache_size_budget`
+/// are willing to evict: raw `.wav`/`.flac` scratch recordings and
+/// `compress_cache`'s retained `.wav.zz`/`.flac.zz` copies.
+fn is_cached_recording(path: &Path) -> bool {
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("wav") | Some("flac") => true,
+        Some("zz") => matches!(
+            path.file_stem().map(Path::new).and_then(|p| p.extension()).and_then(|ext| ext.to_str()),
+            Some("wav") | Some("flac")
+        ),
+        _ => false,
+    }
+}
+
+/// After age-based cleanup, if `dir`'s cached recordings still total more
+/// than `max_bytes`, evicts the oldest ones (by mtime) until back under
+/// budget. Returns the number of files removed.
+pub async fn enforce_cache_size_budget(dir: &Path, max_bytes: u64) -> Result<usize> {
+    let mut entries = fs::read_dir(dir)
+        .await
+        .with_context(|| format!("Failed to read cache directory: {}", dir.display()))?;
+
+    let mut files = Vec::new();
+    let mut total: u64 = 0;
+    while let Some(entry) = entries.next_entry().await? {
+        let path = entry.path();
+        if !path.is_file() || !is_cached_recording(&path) {
+            continue;
+        }
+        let metadata = fs::metadata(&path).await?;
+        let modified = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+        total += metadata.len();
+        files.push((path, metadata.len(), modified));
+    }
+
+    if total <= max_bytes {
+        return Ok(0);
+    }
+
+    files.sort_by_key(|(_, _, modified)| *modified);
+    let mut removed = 0;
+    for (path, size, _) in files {
+        if total <= max_bytes {
+            break;
+        }
+        debug!("Evicting cached recording to stay under the cache size budget: {}", path.display());
+        if let Err(e) = fs::remove_file(&path).await {
+            log::warn!("Failed to evict cached file {}: {}", path.display(), e);
+            continue;
+        }
+        total = total.saturating_sub(size);
+        removed += 1;
+    }
+    Ok(removed)
+}
+
+/// Deflate-compresses `path` into a sibling file with `.zz` appended to its
+/// name, then removes the uncompressed original. Returns the compressed
+/// file's path.
+pub async fn compress_cached_file(path: &Path) -> Result<PathBuf> {
+    let data = fs::read(path)
+        .await
+        .with_context(|| format!("Failed to read {} for compression", path.display()))?;
+    let mut compressed_name = path.as_os_str().to_os_string();
+    compressed_name.push(".zz");
+    let compressed_path = PathBuf::from(compressed_name);
+
+    let compressed = tokio::task::spawn_blocking(move || -> Result<Vec<u8>> {
+        let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&data).context("Failed to deflate-compress cached recording")?;
+        encoder.finish().context("Failed to finalize deflate compression")
+    })
+    .await
+    .context("Compression task panicked")??;
+
+    fs::write(&compressed_path, compressed)
+        .await
+        .with_context(|| format!("Failed to write {}", compressed_path.display()))?;
+    fs::remove_file(path)
+        .await
+        .with_context(|| format!("Failed to remove uncompressed {}", path.display()))?;
+    Ok(compressed_path)
+}
+
+/// Inflates a `.zz`-suffixed cached file back to a plain file at the same
+/// path with the `.zz` suffix stripped, for a provider upload that needs a
+/// real audio file on disk. Leaves the compressed original in place.
+pub async fn decompress_cached_file(compressed_path: &Path) -> Result<PathBuf> {
+    let data = fs::read(compressed_path)
+        .await
+        .with_context(|| format!("Failed to read {}", compressed_path.display()))?;
+    let plain_path = compressed_path.with_extension(""); // strips the trailing ".zz"
+
+    let decompressed = tokio::task::spawn_blocking(move || -> Result<Vec<u8>> {
+        let mut decoder = DeflateDecoder::new(&data[..]);
+        let mut out = Vec::new();
+        decoder.read_to_end(&mut out).context("Failed to inflate cached recording")?;
+        Ok(out)
+    })
+    .await
+    .context("Decompression task panicked")??;
+
+    fs::write(&plain_path, decompressed)
+        .await
+        .with_context(|| format!("Failed to write {}", plain_path.display()))?;
+    Ok(plain_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write as _;
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    /// A fresh, empty directory under the OS temp dir, unique per call so
+    /// tests running concurrently don't see each other's files.
+    fn unique_test_dir(label: &str) -> PathBuf {
+        static COUNTER: AtomicU64 = AtomicU64::new(0);
+        let n = COUNTER.fetch_add(1, Ordering::Relaxed);
+        let dir = std::env::temp_dir().join(format!("whisper_dictation_test_{}_{}_{}", std::process::id(), label, n));
+        std::fs::create_dir_all(&dir).expect("failed to create test temp dir");
+        dir
+    }
+
+    fn write_file(dir: &Path, name: &str, bytes: usize) -> PathBuf {
+        let path = dir.join(name);
+        let mut file = std::fs::File::create(&path).expect("failed to create test file");
+        file.write_all(&vec![0u8; bytes]).expect("failed to write test file");
+        path
+    }
+
+    #[test]
+    fn is_cached_recording_matches_known_extensions_only() {
+        let dir = unique_test_dir("is_cached");
+        assert!(is_cached_recording(&dir.join("a.wav")));
+        assert!(is_cached_recording(&dir.join("a.flac")));
+        assert!(is_cached_recording(&dir.join("a.wav.zz")));
+        assert!(is_cached_recording(&dir.join("a.flac.zz")));
+        assert!(!is_cached_recording(&dir.join("a.txt")));
+        assert!(!is_cached_recording(&dir.join("a.mp3.zz")));
+        assert!(!is_cached_recording(&dir.join("a")));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn enforce_cache_size_budget_evicts_oldest_first() {
+        let dir = unique_test_dir("evict");
+        write_file(&dir, "oldest.wav", 100);
+        tokio::time::sleep(Duration::from_millis(1100)).await;
+        write_file(&dir, "middle.wav", 100);
+        tokio::time::sleep(Duration::from_millis(1100)).await;
+        write_file(&dir, "newest.wav", 100);
+
+        // 300 bytes total; a 100-byte budget should only leave room for the
+        // newest file, evicting the other two oldest-first.
+        let removed = enforce_cache_size_budget(&dir, 100)
+            .await
+            .expect("enforce_cache_size_budget failed");
+        assert_eq!(removed, 2);
+        assert!(!dir.join("oldest.wav").exists());
+        assert!(!dir.join("middle.wav").exists());
+        assert!(dir.join("newest.wav").exists());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn enforce_cache_size_budget_is_a_noop_under_budget() {
+        let dir = unique_test_dir("noop");
+        write_file(&dir, "a.wav", 50);
+        write_file(&dir, "b.flac", 50);
+
+        let removed = enforce_cache_size_budget(&dir, 1000)
+            .await
+            .expect("enforce_cache_size_budget failed");
+        assert_eq!(removed, 0);
+        assert!(dir.join("a.wav").exists());
+        assert!(dir.join("b.flac").exists());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
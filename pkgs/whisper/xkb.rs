@@ -0,0 +1,65 @@
+use anyhow::{anyhow, Result};
+use xkbcommon::xkb;
+
+/// Resolves a user-supplied key name (e.g. `"F11"`, `"at"`, `"adiaeresis"`) to
+/// the evdev keycode(s) that currently produce it, using the system's active
+/// XKB keymap instead of the small hard-coded `parse_key_evdev` table. This
+/// is what lets a binding target a dead key or a non-US-layout symbol. The
+/// keymap is compiled once, so a layout change takes effect only when the
+/// listener is restarted.
+pub struct KeyResolver {
+    keymap: xkb::Keymap,
+}
+
+impl KeyResolver {
+    /// Compiles the keymap from the system's default rules/model/layout/
+    /// variant/options (the `XKB_DEFAULT_*` env vars, or the system
+    /// config if unset) rather than from a specific compositor handle.
+    pub fn from_system_rmlvo() -> Result<Self> {
+        let context = xkb::Context::new(xkb::CONTEXT_NO_FLAGS);
+        let keymap = xkb::Keymap::new_from_names(
+            &context,
+            "", // rules
+            "", // model
+            "", // layout
+            "", // variant
+            None,
+            xkb::KEYMAP_COMPILE_NO_FLAGS,
+        )
+        .ok_or_else(|| anyhow!("Failed to compile XKB keymap from system rules/model/layout"))?;
+        Ok(Self { keymap })
+    }
+
+    /// Returns every evdev keycode whose layout-0/level-0 keysym matches
+    /// `key_name`. Most keysyms come from exactly one key, but a keysym that
+    /// several keys produce at that level yields all of their codes.
+    pub fn resolve(&self, key_name: &str) -> Result<Vec<u32>> {
+        let keysym = xkb::keysym_from_name(key_name, xkb::KEYSYM_NO_FLAGS);
+        if keysym == xkb::Keysym::from(xkb::KEY_NoSymbol) {
+            return Err(anyhow!("Unknown XKB keysym name: {}", key_name));
+        }
+
+        let min = self.keymap.min_keycode();
+        let max = self.keymap.max_keycode();
+        let mut matches = Vec::new();
+        let mut code = min.raw();
+        while code <= max.raw() {
+            let xkb_code = xkb::Keycode::new(code);
+            let syms = self.keymap.key_get_syms_by_level(xkb_code, 0, 0);
+            if syms.contains(&keysym) {
+                // XKB keycodes are evdev keycodes offset by 8, a historical
+                // X11 convention that libxkbcommon keeps for compatibility.
+                matches.push(code.saturating_sub(8));
+            }
+            code += 1;
+        }
+
+        if matches.is_empty() {
+            return Err(anyhow!(
+                "No key on the active layout currently produces '{}'",
+                key_name
+            ));
+        }
+        Ok(matches)
+    }
+}